@@ -0,0 +1,244 @@
+use crate::operations::{Instruction, JumpTarget, Operand};
+use crate::Program;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The JSON representation of a parsed program.
+#[derive(Serialize)]
+struct ProgramJson<'a> {
+    /// The name of the program, usually the path of its source file.
+    name: &'a str,
+    /// The parsed lines of the program, in order.
+    lines: Vec<LineJson<'a>>,
+    /// Map of label names to the 1-indexed lines they are defined on.
+    labels: BTreeMap<&'a str, usize>,
+    /// Map of the names of constants defined in the program to their values.
+    constants: BTreeMap<&'a str, i32>,
+}
+
+/// The JSON representation of a single parsed line.
+#[derive(Serialize)]
+struct LineJson<'a> {
+    /// The 1-indexed line number.
+    line: usize,
+    /// The normalized source code of the line.
+    source: &'a str,
+    /// The name of the operation the line matched.
+    operation: &'static str,
+    /// The parsed operands of the operation, in the order the operation uses them rather than the order they're written in.
+    operands: Vec<OperandJson<'a>>,
+}
+
+/// The JSON representation of an operand.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OperandJson<'a> {
+    /// The name of a register.
+    Register { name: &'a str },
+    /// A literal value.
+    Literal { value: i32 },
+    /// The name of a label, along with the 1-indexed line it's defined on.
+    Label { name: &'a str, line: usize },
+}
+
+/// Converts a parsed program into JSON that describes the operation and operands of each line, where its labels are, and the values of its constants.
+///
+/// # Arguments
+/// * `program`: The program to convert.
+/// * `name`: The name of the program, usually the path of its source file.
+///
+/// Returns the JSON, pretty-printed.
+pub fn to_json(program: &Program, name: &str) -> String {
+    let lines = program
+        .instructions
+        .iter()
+        .enumerate()
+        .map(|(line_number, instruction)| {
+            let (operation, operands) = describe(instruction, line_number);
+            LineJson {
+                line: line_number + 1,
+                source: &program.source[line_number],
+                operation,
+                operands,
+            }
+        })
+        .collect();
+    let labels = program
+        .labels
+        .iter()
+        .map(|(label, line_number)| (label.as_str(), line_number + 1))
+        .collect();
+    let constants = program
+        .constants
+        .iter()
+        .map(|(name, value)| (name.as_str(), *value))
+        .collect();
+
+    let program = ProgramJson {
+        name,
+        lines,
+        labels,
+        constants,
+    };
+    serde_json::to_string_pretty(&program).expect("cannot serialize program")
+}
+
+/// Gets the name of an instruction's operation and its operands.
+/// The name is one of the ones in `OPERATIONS`. Operations that are shorthand for another one, like `clear`, can't be told apart from it once they're parsed,
+/// so they're described as the operation they're shorthand for.
+///
+/// # Arguments
+/// * `instruction`: The instruction to describe.
+/// * `line_number`: The 0-indexed line number the instruction is on.
+fn describe(instruction: &Instruction, line_number: usize) -> (&'static str, Vec<OperandJson<'_>>) {
+    match instruction {
+        Instruction::Label(name) => (
+            "label",
+            vec![OperandJson::Label {
+                name,
+                line: line_number + 1,
+            }],
+        ),
+        Instruction::DefineConstant => ("define_constant", vec![]),
+        Instruction::Increment(register) => ("increment", vec![register_json(register)]),
+        Instruction::Decrement(register) => ("decrement", vec![register_json(register)]),
+        Instruction::Negate(register) => ("negate", vec![register_json(register)]),
+        Instruction::Complement(register) => ("complement", vec![register_json(register)]),
+        Instruction::Double(register) => ("double", vec![register_json(register)]),
+        Instruction::Halve(register) => ("halve", vec![register_json(register)]),
+        Instruction::ShiftLeft(register, amount) => (
+            "shift_left",
+            vec![register_json(register), operand_json(amount)],
+        ),
+        Instruction::ShiftRight(register, amount) => (
+            "shift_right",
+            vec![register_json(register), operand_json(amount)],
+        ),
+        Instruction::SquareRoot(register) => ("isqrt", vec![register_json(register)]),
+        Instruction::Randomize(register, upper_bound) => (
+            "randomize",
+            vec![
+                register_json(register),
+                OperandJson::Literal {
+                    value: *upper_bound,
+                },
+            ],
+        ),
+        Instruction::Assign(register, value) => {
+            ("assign", vec![register_json(register), operand_json(value)])
+        }
+        Instruction::AssignLabelLine(register, target) => {
+            ("assign", vec![register_json(register), target_json(target)])
+        }
+        Instruction::Swap(first, second) => {
+            ("swap", vec![register_json(first), register_json(second)])
+        }
+        Instruction::Add(register, other) => {
+            ("add", vec![register_json(register), operand_json(other)])
+        }
+        Instruction::Subtract(register, other) => (
+            "subtract",
+            vec![register_json(register), operand_json(other)],
+        ),
+        Instruction::Min(register, other) => {
+            ("min", vec![register_json(register), register_json(other)])
+        }
+        Instruction::Max(register, other) => {
+            ("max", vec![register_json(register), register_json(other)])
+        }
+        Instruction::Divide(register, divisor) => (
+            "divide",
+            vec![register_json(register), operand_json(divisor)],
+        ),
+        Instruction::Modulo(register, divisor) => (
+            "modulo",
+            vec![register_json(register), operand_json(divisor)],
+        ),
+        Instruction::And(register, other) => (
+            "bitwise_and",
+            vec![register_json(register), register_json(other)],
+        ),
+        Instruction::Or(register, other) => (
+            "bitwise_or",
+            vec![register_json(register), register_json(other)],
+        ),
+        Instruction::Xor(register, other) => (
+            "bitwise_xor",
+            vec![register_json(register), register_json(other)],
+        ),
+        Instruction::Read(register) => ("read", vec![register_json(register)]),
+        Instruction::ReadWord(register) => ("read_word", vec![register_json(register)]),
+        Instruction::ReadLine(register) => ("read_line", vec![register_json(register)]),
+        Instruction::ReadInt(register) => ("read_int", vec![register_json(register)]),
+        Instruction::ReadAll(register) => ("read_all", vec![register_json(register)]),
+        Instruction::Print(register) => ("print", vec![register_json(register)]),
+        Instruction::PrintHex(register) => ("print_hex", vec![register_json(register)]),
+        Instruction::PrintAll(registers) => (
+            "print_all",
+            registers
+                .iter()
+                .map(|register| register_json(register))
+                .collect(),
+        ),
+        Instruction::Noop => ("noop", vec![]),
+        Instruction::Halt => ("halt", vec![]),
+        Instruction::Jump(target) => ("jump", vec![target_json(target)]),
+        Instruction::JumpIndirect(register) => ("jump_indirect", vec![register_json(register)]),
+        Instruction::Call(target) => ("call", vec![target_json(target)]),
+        Instruction::Return => ("ret", vec![]),
+        Instruction::JumpIfZero(register, target) => (
+            "jump_if_zero",
+            vec![register_json(register), target_json(target)],
+        ),
+        Instruction::JumpIfNotZero(register, target) => (
+            "jump_if_not_zero",
+            vec![register_json(register), target_json(target)],
+        ),
+        Instruction::JumpIfNegative(register, target) => (
+            "jump_if_neg",
+            vec![register_json(register), target_json(target)],
+        ),
+        Instruction::JumpIfPositive(register, target) => (
+            "jump_if_pos",
+            vec![register_json(register), target_json(target)],
+        ),
+        Instruction::JumpIfEqual(register, value, target) => (
+            "jump_if_equal",
+            vec![
+                register_json(register),
+                operand_json(value),
+                target_json(target),
+            ],
+        ),
+        Instruction::JumpIfGreater(first, second, target) => (
+            "jump_if_greater",
+            vec![
+                register_json(first),
+                register_json(second),
+                target_json(target),
+            ],
+        ),
+    }
+}
+
+/// Gets the JSON representation of the register with the provided name.
+fn register_json(name: &str) -> OperandJson<'_> {
+    OperandJson::Register { name }
+}
+
+/// Gets the JSON representation of an operand.
+fn operand_json(operand: &Operand) -> OperandJson<'_> {
+    match operand {
+        Operand::Register(name) => OperandJson::Register { name },
+        Operand::Literal(value) => OperandJson::Literal { value: *value },
+        Operand::Label(_) => unreachable!("labels are never parsed as values"),
+    }
+}
+
+/// Gets the JSON representation of the label a jump goes to.
+fn target_json(target: &JumpTarget) -> OperandJson<'_> {
+    OperandJson::Label {
+        name: &target.label,
+        line: target.line_number + 1,
+    }
+}
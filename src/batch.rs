@@ -0,0 +1,117 @@
+use crate::{run_source_with_io, Error, Settings};
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+/// The extensions of files containing source code.
+const SOURCE_EXTENSIONS: &[&str] = &["sc", "business"];
+
+/// The extension of files containing the output a program is expected to produce.
+const EXPECTED_EXTENSION: &str = "expected";
+
+/// The extension of files containing the input to run a program with.
+const INPUT_EXTENSION: &str = "input";
+
+/// Runs every program in a directory that has a file with its expected output next to it, and writes whether each one passed to `output`.
+/// A program passes if it runs without errors and writes exactly what's in its `.expected` file.
+/// If a program has an `.input` file next to it, the program reads from that; otherwise it reads from `settings.input`, or from nothing if that isn't set.
+///
+/// # Arguments
+/// * `dir`: The path of the directory containing the programs.
+/// * `settings`: Settings that control how the programs are run. `record` is ignored, since every program would write to the same file.
+/// * `output`: Where the results should be written to.
+///
+/// Returns whether every program passed, or `Err(Error)` if the directory or any of the files in it can't be read, or writing to `output` fails.
+pub fn run(dir: &str, settings: &Settings, output: &mut dyn Write) -> Result<bool, Error> {
+    let mut passed = 0;
+    let mut failed = 0;
+    for path in find_programs(dir)? {
+        let result = run_program(&path, settings)?;
+        let line = match &result {
+            Ok(()) => format!("pass\t{}", path.display()),
+            Err(reason) => format!("fail\t{}\t{}", path.display(), reason),
+        };
+        writeln!(output, "{}", line).map_err(write_error)?;
+        if result.is_ok() {
+            passed += 1;
+        } else {
+            failed += 1;
+        }
+    }
+    writeln!(output, "{} passed, {} failed", passed, failed).map_err(write_error)?;
+    Ok(failed == 0)
+}
+
+/// Finds the programs in a directory that have a file with their expected output next to them, sorted by path.
+fn find_programs(dir: &str) -> Result<Vec<PathBuf>, Error> {
+    let entries = fs::read_dir(dir).map_err(|e| read_error(Path::new(dir), e))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| read_error(Path::new(dir), e))?.path();
+        let is_source = path
+            .extension()
+            .is_some_and(|x| SOURCE_EXTENSIONS.iter().any(|extension| x == *extension));
+        if is_source && path.with_extension(EXPECTED_EXTENSION).is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Runs a program and compares its output to what's expected.
+///
+/// # Arguments
+/// * `path`: The path of the file containing the program's source code.
+/// * `settings`: Settings that control how the program is run.
+///
+/// Returns `Ok(())` if the program passed, or `Err` describing why it failed.
+/// Returns `Err(Error)` instead if any of the program's files can't be read.
+fn run_program(path: &Path, settings: &Settings) -> Result<Result<(), String>, Error> {
+    let source = fs::read_to_string(path).map_err(|e| read_error(path, e))?;
+    let expected_path = path.with_extension(EXPECTED_EXTENSION);
+    let expected = fs::read(&expected_path).map_err(|e| read_error(&expected_path, e))?;
+    let input_path = path.with_extension(INPUT_EXTENSION);
+    let input = if input_path.is_file() {
+        fs::read(&input_path).map_err(|e| read_error(&input_path, e))?
+    } else {
+        settings.input.clone().unwrap_or_default()
+    };
+
+    let settings = Settings {
+        file: Some(path.display().to_string()),
+        record: None,
+        ..settings.clone()
+    };
+    let mut actual = Vec::new();
+    let result = run_source_with_io(
+        &source,
+        settings,
+        Box::new(Cursor::new(input)),
+        Box::new(&mut actual),
+    );
+    // errors span several lines, but only the first one is needed to say what went wrong
+    if let Err(e) = result {
+        let message = e.to_string();
+        return Ok(Err(message.lines().next().unwrap_or("").to_string()));
+    }
+
+    if actual == expected {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(format!(
+            "output does not match {}",
+            expected_path.display()
+        )))
+    }
+}
+
+/// Builds the error for a file or directory that can't be read.
+fn read_error(path: &Path, e: std::io::Error) -> Error {
+    Error::new(&format!("cannot read {}: {}", path.display(), e), 0)
+}
+
+/// Builds the error for results that can't be written.
+fn write_error(e: std::io::Error) -> Error {
+    Error::new(&format!("error writing to stdout: {}", e), 0)
+}
@@ -0,0 +1,129 @@
+use crate::include::Origin;
+use crate::operations::Instruction;
+use crate::Program;
+use std::fmt::Write;
+
+/// Converts a parsed program into a Graphviz DOT graph of its control flow.
+/// Each node is a block of lines that always run one after another, starting at the start of the program, at a label, or after a jump.
+/// Each edge is either a fallthrough to the next block or a jump, labeled with the condition the jump depends on.
+///
+/// # Arguments
+/// * `program`: The program to convert.
+/// * `origins`: Where each line of the program was read from, which is shown next to the line.
+///
+/// Returns the DOT source code.
+pub fn to_dot(program: &Program, origins: &[Origin]) -> String {
+    let instructions = &program.instructions;
+    let block_starts = find_block_starts(program);
+    let block_of = |line_number: usize| match block_starts.binary_search(&line_number) {
+        Ok(x) => x,
+        Err(x) => x - 1,
+    };
+    let label_blocks: Vec<usize> = program
+        .label_line_numbers()
+        .iter()
+        .copied()
+        .map(block_of)
+        .collect();
+
+    let mut dot = String::new();
+    writeln!(dot, "digraph program {{").unwrap();
+    writeln!(dot, "    node [shape=box, fontname=monospace];").unwrap();
+    writeln!(dot, "    start [shape=oval];").unwrap();
+    writeln!(dot, "    end [shape=oval];").unwrap();
+    writeln!(dot, "    start -> block0;").unwrap();
+    for (block, start) in block_starts.iter().enumerate() {
+        let end = block_starts
+            .get(block + 1)
+            .copied()
+            .unwrap_or(instructions.len());
+        let lines: String = (*start..end)
+            .map(|line_number| {
+                format!(
+                    "{}: {}\\l",
+                    escape(&origins[line_number].to_string()),
+                    escape(&program.source[line_number])
+                )
+            })
+            .collect();
+        writeln!(dot, "    block{} [label=\"{}\"];", block, lines).unwrap();
+
+        let last = &instructions[end - 1];
+        match last {
+            Instruction::JumpIndirect(_) => {
+                for label_block in &label_blocks {
+                    writeln!(
+                        dot,
+                        "    block{} -> block{} [style=dashed];",
+                        block, label_block
+                    )
+                    .unwrap();
+                }
+            }
+            Instruction::Halt => writeln!(dot, "    block{} -> end;", block).unwrap(),
+            _ => {
+                if let Some(target) = last.jump_target() {
+                    let edge_label = jump_condition(last).map_or(String::new(), |condition| {
+                        format!(" [label=\"{}\"]", condition)
+                    });
+                    writeln!(
+                        dot,
+                        "    block{} -> block{}{};",
+                        block,
+                        block_of(target.line_number),
+                        edge_label
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        // a return goes back to the line after whichever call was made last, which isn't known until the program runs
+        if !last.always_jumps() {
+            if end < instructions.len() {
+                writeln!(dot, "    block{} -> block{};", block, block + 1).unwrap();
+            } else {
+                writeln!(dot, "    block{} -> end;", block).unwrap();
+            }
+        }
+    }
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+/// Finds the 0-indexed line numbers that blocks start on, in order.
+/// A block starts at the first line of the program, at every label, and after every line that can jump.
+fn find_block_starts(program: &Program) -> Vec<usize> {
+    let mut block_starts = vec![0];
+    for (line_number, instruction) in program.instructions.iter().enumerate() {
+        let previous = match line_number.checked_sub(1) {
+            Some(x) => &program.instructions[x],
+            None => continue,
+        };
+        if matches!(instruction, Instruction::Label(_))
+            || previous.jump_target().is_some()
+            || previous.always_jumps()
+        {
+            block_starts.push(line_number);
+        }
+    }
+    block_starts
+}
+
+/// Gets the label for the edge of an instruction's jump, or `None` if the jump always happens.
+fn jump_condition(instruction: &Instruction) -> Option<&'static str> {
+    match instruction {
+        Instruction::Call(_) => Some("call"),
+        Instruction::JumpIfZero(..) => Some("zero"),
+        Instruction::JumpIfNotZero(..) => Some("not zero"),
+        Instruction::JumpIfNegative(..) => Some("negative"),
+        Instruction::JumpIfPositive(..) => Some("positive"),
+        Instruction::JumpIfEqual(..) => Some("equal"),
+        Instruction::JumpIfGreater(..) => Some("greater"),
+        _ => None,
+    }
+}
+
+/// Escapes a string so it can be used inside a quoted DOT string.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
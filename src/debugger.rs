@@ -0,0 +1,100 @@
+use crate::{Context, Error};
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// A command entered while a program is paused in the debugger.
+enum Command {
+    /// Execute the current line, then pause again.
+    Step,
+    /// Execute the rest of the program without pausing.
+    Continue,
+    /// Show the value of a register or constant, or of every register if none is named.
+    Print(Option<String>),
+    /// Stop the program without executing any more lines.
+    Quit,
+}
+
+/// Pauses before executing the current line, or at the end of the program if there are no more lines, and reads commands until one of them resumes or stops the program.
+/// If there are no more commands to read, the rest of the program is executed without pausing.
+///
+/// Returns `Ok(true)` if the current line should be executed, or `Ok(false)` if the program should stop.
+pub fn pause(context: &mut Context) -> Result<bool, Error> {
+    // make sure output from previous lines shows up before the debugger output
+    context.flush_output()?;
+
+    let line_number = context.current_line_number;
+    match context.program.source.get(line_number) {
+        Some(line) => eprintln!("{}: {}", context.location(line_number), line),
+        None => eprintln!("end of program"),
+    }
+    eprintln!("{}", context.describe_registers());
+
+    loop {
+        eprint!("(debug) ");
+        let mut command = String::new();
+        match context.input.read_line(&mut command) {
+            Ok(0) => {
+                eprintln!();
+                context.stepping = false;
+                return Ok(true);
+            }
+            Ok(_) => (),
+            Err(e) => {
+                return Err(Error::new(
+                    &format!("error reading debugger command: {}", e),
+                    line_number,
+                ))
+            }
+        }
+
+        match parse_command(&command) {
+            Ok(Command::Step) => return Ok(true),
+            Ok(Command::Continue) => {
+                context.stepping = false;
+                return Ok(true);
+            }
+            Ok(Command::Print(None)) => eprintln!("{}", context.describe_registers()),
+            Ok(Command::Print(Some(name))) => {
+                // names are stored lowercased unless the program is case-sensitive, so try the name both as entered and lowercased
+                let lowercase_name = name.to_lowercase();
+                let find = |values: &HashMap<String, i32>| {
+                    values
+                        .get(&name)
+                        .or_else(|| values.get(&lowercase_name))
+                        .copied()
+                };
+                if let Some(value) = find(&context.registers) {
+                    eprintln!("{} = {}", name, value);
+                } else if let Some(value) = find(&context.program.constants) {
+                    eprintln!("{} = {} (constant)", name, value);
+                } else {
+                    eprintln!("unknown register or constant: {}", name);
+                }
+            }
+            Ok(Command::Quit) => return Ok(false),
+            Err(message) => eprintln!("{}", message),
+        }
+    }
+}
+
+/// Parses a debugger command.
+/// An empty command is the same as `step`.
+///
+/// Returns `Err(String)` describing the valid commands if the command isn't one of them.
+fn parse_command(command: &str) -> Result<Command, String> {
+    let command = command.trim();
+    let (name, argument) = match command.find(' ') {
+        Some(x) => (command[..x].to_lowercase(), command[x + 1..].trim()),
+        None => (command.to_lowercase(), ""),
+    };
+    match (name.as_str(), argument) {
+        ("", "") | ("step", "") | ("s", "") => Ok(Command::Step),
+        ("continue", "") | ("c", "") => Ok(Command::Continue),
+        ("print", "") | ("p", "") => Ok(Command::Print(None)),
+        ("print", name) | ("p", name) => Ok(Command::Print(Some(name.to_string()))),
+        ("quit", "") | ("q", "") => Ok(Command::Quit),
+        _ => Err(
+            "valid commands are step, continue, print [register or constant], and quit".to_string(),
+        ),
+    }
+}
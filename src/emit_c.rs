@@ -0,0 +1,1041 @@
+use crate::include::Origin;
+use crate::operations::{Instruction, JumpTarget, Operand};
+use crate::{
+    all_register_names, DivisionBehavior, OverflowBehavior, Program, Settings, REGISTER_NAMES,
+};
+use std::collections::{HashMap, HashSet};
+
+/// A function that emitted programs can call.
+struct Helper {
+    /// The name of the function.
+    name: &'static str,
+    /// The names of the other helpers the function calls.
+    dependencies: &'static [&'static str],
+    /// The C source code of the function.
+    source: &'static str,
+}
+
+/// The helpers that emitted programs can call, in an order where every helper comes after its dependencies.
+/// The source code of `fit` depends on the overflow behavior, so it's filled in by `fit_source`, the source code of `args` is filled in by `args_source`, and the source code of `fail` is filled in by `fail_source`.
+const HELPERS: [Helper; 25] = [
+    Helper {
+        name: "fail",
+        dependencies: &[],
+        source: "",
+    },
+    Helper {
+        name: "fit",
+        dependencies: &[],
+        source: "",
+    },
+    Helper {
+        name: "args",
+        dependencies: &[],
+        source: "",
+    },
+    Helper {
+        name: "byte_queue",
+        dependencies: &[],
+        source: r#"typedef struct {
+    unsigned char *data;
+    size_t start;
+    size_t end;
+    size_t capacity;
+} byte_queue;
+
+static int peek_byte(const byte_queue *queue) {
+    return queue->start == queue->end ? -1 : queue->data[queue->start];
+}
+
+static int pop_byte(byte_queue *queue) {
+    int b = peek_byte(queue);
+    if (b != -1 && ++queue->start == queue->end) {
+        queue->start = 0;
+        queue->end = 0;
+    }
+    return b;
+}
+
+/* bytes that have been read from stdin but not stored in a register yet */
+static byte_queue input_buffer;"#,
+    },
+    Helper {
+        name: "push_byte",
+        dependencies: &["byte_queue"],
+        source: r#"static void push_byte(byte_queue *queue, int b) {
+    if (queue->end == queue->capacity) {
+        queue->capacity = queue->capacity == 0 ? 64 : queue->capacity * 2;
+        queue->data = realloc(queue->data, queue->capacity);
+        if (queue->data == NULL) {
+            fputs("out of memory\n", stderr);
+            exit(EXIT_FAILURE);
+        }
+    }
+    queue->data[queue->end++] = (unsigned char)b;
+}"#,
+    },
+    Helper {
+        name: "divide",
+        dependencies: &["fail", "fit"],
+        source: r#"static int32_t divide(int32_t value, int32_t divisor, int line) {
+    int64_t quotient;
+    if (divisor == 0) {
+        fail(line, "division by zero");
+    }
+    quotient = (int64_t)value / divisor;
+#if FLOOR_DIVISION
+    /* the quotient was rounded toward zero, which is up instead of down if it's negative and inexact */
+    if ((int64_t)value % divisor != 0 && (value < 0) != (divisor < 0)) {
+        quotient--;
+    }
+#endif
+    return fit(quotient, line);
+}"#,
+    },
+    Helper {
+        name: "modulo",
+        dependencies: &["fail"],
+        source: r#"static int32_t modulo(int32_t value, int32_t divisor, int line) {
+    int32_t remainder;
+    if (divisor == 0) {
+        fail(line, "division by zero");
+    }
+    /* INT32_MIN % -1 overflows in C, but is mathematically 0 */
+    remainder = divisor == -1 ? 0 : value % divisor;
+#if FLOOR_DIVISION
+    /* the remainder has the sign of the value, but rounding the quotient down means it has the sign of the divisor instead */
+    if (remainder != 0 && (remainder < 0) != (divisor < 0)) {
+        remainder += divisor;
+    }
+#endif
+    return remainder;
+}"#,
+    },
+    Helper {
+        name: "check_shift",
+        dependencies: &["fail"],
+        source: r#"static void check_shift(int32_t amount, int line) {
+    if (amount < 0 || amount > 31) {
+        fail(line, "invalid shift amount: %" PRId32, amount);
+    }
+}"#,
+    },
+    Helper {
+        name: "shift_left",
+        dependencies: &["check_shift", "fit"],
+        source: r#"static int32_t shift_left(int32_t value, int32_t amount, int line) {
+    check_shift(amount, line);
+    /* shifting left multiplies by a power of 2, so it can overflow the same way */
+    return fit((int64_t)value * ((int64_t)1 << amount), line);
+}"#,
+    },
+    Helper {
+        name: "shift_right",
+        dependencies: &["check_shift"],
+        source: r#"static int32_t shift_right(int32_t value, int32_t amount, int line) {
+    check_shift(amount, line);
+    /* right shifting a negative number isn't portable, so keep the sign by shifting its complement instead */
+    return value < 0 ? ~(~value >> amount) : value >> amount;
+}"#,
+    },
+    Helper {
+        name: "square_root",
+        dependencies: &["fail"],
+        source: r#"static int32_t square_root(int32_t value, int line) {
+    int64_t guess;
+    int64_t next_guess;
+    if (value < 0) {
+        fail(line, "cannot take the square root of %" PRId32, value);
+    }
+    if (value < 2) {
+        return value;
+    }
+    guess = value;
+    next_guess = (guess + 1) / 2;
+    while (next_guess < guess) {
+        guess = next_guess;
+        next_guess = (guess + value / guess) / 2;
+    }
+    return (int32_t)guess;
+}"#,
+    },
+    Helper {
+        name: "minimum",
+        dependencies: &[],
+        source: r#"static int32_t minimum(int32_t a, int32_t b) {
+    return a < b ? a : b;
+}"#,
+    },
+    Helper {
+        name: "maximum",
+        dependencies: &[],
+        source: r#"static int32_t maximum(int32_t a, int32_t b) {
+    return a > b ? a : b;
+}"#,
+    },
+    Helper {
+        name: "random_up_to",
+        dependencies: &[],
+        source: r#"static int32_t random_up_to(int32_t upper_bound) {
+    uint64_t random = (uint64_t)rand() * ((uint64_t)RAND_MAX + 1) + (uint64_t)rand();
+    return (int32_t)(random % ((uint64_t)upper_bound + 1));
+}"#,
+    },
+    Helper {
+        name: "next_byte",
+        dependencies: &["byte_queue"],
+        source: r#"static int next_byte(void) {
+    int b = pop_byte(&input_buffer);
+    if (b != -1) {
+        return b;
+    }
+    /* make sure any prompts are visible before waiting for input */
+    fflush(stdout);
+    b = getchar();
+    return b == EOF ? -1 : b;
+}"#,
+    },
+    Helper {
+        name: "read_byte",
+        dependencies: &["args", "next_byte"],
+        source: r#"static int32_t read_byte(void) {
+    int32_t arg;
+    int b;
+    if (pop_arg(&arg)) {
+        return arg;
+    }
+    b = next_byte();
+    return b == -1 ? EOF_VALUE : b;
+}"#,
+    },
+    Helper {
+        name: "read_word",
+        dependencies: &["args", "next_byte"],
+        source: r#"static int32_t read_word(void) {
+    int32_t arg;
+    int32_t value;
+    int high;
+    int low;
+    if (pop_arg(&arg)) {
+        return arg;
+    }
+    high = next_byte();
+    low = high == -1 ? -1 : next_byte();
+    if (low == -1) {
+        return EOF_VALUE;
+    }
+    value = high << 8 | low;
+    return value >= 0x8000 ? value - 0x10000 : value;
+}"#,
+    },
+    Helper {
+        name: "read_line",
+        dependencies: &["args", "push_byte"],
+        source: r#"static int32_t read_line(void) {
+    int32_t arg;
+    int b;
+    if (pop_arg(&arg)) {
+        return arg;
+    }
+    if (input_buffer.start == input_buffer.end
+        || memchr(input_buffer.data + input_buffer.start, '\n', input_buffer.end - input_buffer.start) == NULL) {
+        /* make sure any prompts are visible before waiting for input */
+        fflush(stdout);
+        do {
+            b = getchar();
+            if (b != EOF) {
+                push_byte(&input_buffer, b);
+            }
+        } while (b != EOF && b != '\n');
+    }
+    b = pop_byte(&input_buffer);
+    return b == -1 ? EOF_VALUE : b;
+}"#,
+    },
+    Helper {
+        name: "read_all",
+        dependencies: &["fail", "push_byte"],
+        source: r#"static int32_t read_all(int line) {
+    int b;
+    /* make sure any prompts are visible before waiting for input */
+    fflush(stdout);
+    while ((b = getchar()) != EOF) {
+        push_byte(&input_buffer, b);
+    }
+    if (input_buffer.end - input_buffer.start > INT32_MAX) {
+        fail(line, "too much input to count");
+    }
+    return (int32_t)(input_buffer.end - input_buffer.start);
+}"#,
+    },
+    Helper {
+        name: "read_int",
+        dependencies: &["args", "fail", "push_byte"],
+        source: r#"static int is_whitespace(int b) {
+    return b == ' ' || b == '\t' || b == '\n' || b == '\f' || b == '\r';
+}
+
+static int32_t read_int(int line) {
+    byte_queue token = { NULL, 0, 0, 0 };
+    const char *digits;
+    int negative;
+    int64_t value = 0;
+    int32_t arg;
+    int b;
+    if (pop_arg(&arg)) {
+        return arg;
+    }
+    /* make sure any prompts are visible before waiting for input */
+    fflush(stdout);
+    while ((b = peek_byte(&input_buffer)) != -1) {
+        if (is_whitespace(b)) {
+            if (token.end != 0) {
+                break;
+            }
+        } else {
+            push_byte(&token, b);
+        }
+        pop_byte(&input_buffer);
+    }
+    if (b == -1) {
+        while ((b = getchar()) != EOF) {
+            if (is_whitespace(b)) {
+                if (token.end != 0) {
+                    /* leave the whitespace after the integer unread */
+                    ungetc(b, stdin);
+                    break;
+                }
+            } else {
+                push_byte(&token, b);
+            }
+        }
+    }
+    if (token.end == 0) {
+        return EOF_VALUE;
+    }
+    push_byte(&token, '\0');
+
+    digits = (const char *)token.data;
+    negative = *digits == '-';
+    if (*digits == '-' || *digits == '+') {
+        digits++;
+    }
+    if (*digits == '\0') {
+        fail(line, "invalid integer: %s", (const char *)token.data);
+    }
+    for (; *digits != '\0'; digits++) {
+        if (*digits < '0' || *digits > '9') {
+            fail(line, "invalid integer: %s", (const char *)token.data);
+        }
+        value = value * 10 + (*digits - '0');
+        if (value > (int64_t)INT32_MAX + negative) {
+            fail(line, "invalid integer: %s", (const char *)token.data);
+        }
+    }
+    free(token.data);
+    return (int32_t)(negative ? -value : value);
+}"#,
+    },
+    Helper {
+        name: "print_value",
+        dependencies: &["fail"],
+        source: r#"static void print_value(int32_t value, int line) {
+    /* negative values, UTF-16 surrogates (0xD800 to 0xDFFF), and values above 0x10FFFF aren't valid characters */
+    if (value < 0 || (value >= 0xD800 && value <= 0xDFFF) || value > 0x10FFFF) {
+        fail(line, "%" PRId32 " does not correspond to a valid UTF-8 character", value);
+    }
+    if (value < 0x80) {
+        putchar(value);
+    } else if (value < 0x800) {
+        putchar(0xC0 | (value >> 6));
+        putchar(0x80 | (value & 0x3F));
+    } else if (value < 0x10000) {
+        putchar(0xE0 | (value >> 12));
+        putchar(0x80 | ((value >> 6) & 0x3F));
+        putchar(0x80 | (value & 0x3F));
+    } else {
+        putchar(0xF0 | (value >> 18));
+        putchar(0x80 | ((value >> 12) & 0x3F));
+        putchar(0x80 | ((value >> 6) & 0x3F));
+        putchar(0x80 | (value & 0x3F));
+    }
+}"#,
+    },
+    Helper {
+        name: "print_hex",
+        dependencies: &[],
+        source: r#"static void print_hex(int32_t value) {
+    /* the magnitude is computed unsigned so the most negative value doesn't overflow */
+    if (value < 0) {
+        printf("-0x%" PRIx32, (uint32_t)0 - (uint32_t)value);
+    } else {
+        printf("0x%" PRIx32, (uint32_t)value);
+    }
+}"#,
+    },
+    Helper {
+        name: "call_stack",
+        dependencies: &[],
+        source: r#"/* the return sites of the calls that haven't been returned from yet, most recent last */
+static int *call_stack = NULL;
+static size_t call_stack_size = 0;"#,
+    },
+    Helper {
+        name: "push_call",
+        dependencies: &["call_stack"],
+        source: r#"static size_t call_stack_capacity = 0;
+
+static void push_call(int return_site) {
+    if (call_stack_size == call_stack_capacity) {
+        call_stack_capacity = call_stack_capacity == 0 ? 64 : call_stack_capacity * 2;
+        call_stack = realloc(call_stack, call_stack_capacity * sizeof(int));
+        if (call_stack == NULL) {
+            fputs("out of memory\n", stderr);
+            exit(EXIT_FAILURE);
+        }
+    }
+    call_stack[call_stack_size++] = return_site;
+}"#,
+    },
+    Helper {
+        name: "pop_call",
+        dependencies: &["fail", "call_stack"],
+        source: r#"static int pop_call(int line) {
+    if (call_stack_size == 0) {
+        fail(line, "cannot return without a matching call");
+    }
+    return call_stack[--call_stack_size];
+}"#,
+    },
+];
+
+/// Builds C source code for a program.
+struct Emitter<'a> {
+    /// The program being converted.
+    program: &'a Program,
+    /// Settings that control how the program is run.
+    settings: &'a Settings,
+    /// Map of register names to the values they start with.
+    initial_registers: &'a HashMap<String, i32>,
+    /// Where each line of the program was read from.
+    origins: &'a [Origin],
+    /// The C statements for the lines of the program emitted so far.
+    body: String,
+    /// The names of the helpers the emitted statements call.
+    helpers: HashSet<&'static str>,
+    /// The names of the registers the emitted statements use.
+    registers: HashSet<String>,
+    /// The 0-indexed line numbers of the labels that can be jumped to.
+    jumped_to_labels: HashSet<usize>,
+    /// The number of calls in the program.
+    call_count: usize,
+    /// Whether the program ever returns from a call.
+    has_return: bool,
+    /// The number of calls emitted so far, used to number the places they return to.
+    calls: usize,
+    /// Whether any of the emitted statements stop the program.
+    halts: bool,
+}
+
+/// Converts a parsed program into the source code of an equivalent C program.
+/// Registers become `int32_t` variables, labels become C labels, and jumps become `goto`s.
+/// Settings that only affect the interpreter, like `debug` and `trace`, are ignored.
+///
+/// # Arguments
+/// * `program`: The program to convert.
+/// * `settings`: Settings that control how the program is run.
+/// * `initial_registers`: Map of register names to the values they start with.
+/// * `origins`: Where each line of the program was read from, used to say where errors occur.
+///
+/// Returns the C source code.
+pub fn emit(
+    program: &Program,
+    settings: &Settings,
+    initial_registers: &HashMap<String, i32>,
+    origins: &[Origin],
+) -> String {
+    let has_indirect_jump = program
+        .instructions
+        .iter()
+        .any(|instruction| matches!(instruction, Instruction::JumpIndirect(_)));
+    // an indirect jump could go to any label
+    let jumped_to_labels = if has_indirect_jump {
+        program.labels.values().copied().collect()
+    } else {
+        program
+            .instructions
+            .iter()
+            .filter_map(|instruction| instruction.jump_target())
+            .map(|target| target.line_number)
+            .collect()
+    };
+
+    let mut emitter = Emitter {
+        program,
+        settings,
+        initial_registers,
+        origins,
+        body: String::new(),
+        helpers: HashSet::new(),
+        registers: HashSet::new(),
+        jumped_to_labels,
+        call_count: program
+            .instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, Instruction::Call(_)))
+            .count(),
+        has_return: program
+            .instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::Return)),
+        calls: 0,
+        halts: false,
+    };
+    for (line_number, instruction) in program.instructions.iter().enumerate() {
+        emitter.emit_line(line_number, instruction);
+    }
+    emitter.finish()
+}
+
+impl<'a> Emitter<'a> {
+    /// Emits the statements for a single line of the program.
+    fn emit_line(&mut self, line_number: usize, instruction: &Instruction) {
+        // `*/` would end the comment early, and compilers warn about `/*` inside a comment
+        let source = self.program.source[line_number]
+            .replace("/*", "/ *")
+            .replace("*/", "* /");
+        let location = self.origins[line_number]
+            .to_string()
+            .replace("/*", "/ *")
+            .replace("*/", "* /");
+        self.body
+            .push_str(&format!("    /* {}: {} */\n", location, source));
+
+        // helpers that fail look up where the line came from in the table built by `fail_source`
+        let line = line_number;
+        let statement = match instruction {
+            Instruction::Label(_) => {
+                if self.jumped_to_labels.contains(&line_number) {
+                    self.body
+                        .push_str(&format!("{}:;\n", label_name(line_number)));
+                }
+                return;
+            }
+            Instruction::DefineConstant | Instruction::Noop => return,
+            Instruction::Increment(register) => {
+                let register = self.register(register);
+                self.arithmetic(&register, &format!("(int64_t){} + 1", register), line)
+            }
+            Instruction::Decrement(register) => {
+                let register = self.register(register);
+                self.arithmetic(&register, &format!("(int64_t){} - 1", register), line)
+            }
+            Instruction::Negate(register) => {
+                let register = self.register(register);
+                self.arithmetic(&register, &format!("-(int64_t){}", register), line)
+            }
+            Instruction::Complement(register) => {
+                let register = self.register(register);
+                format!("{0} = ~{0};", register)
+            }
+            Instruction::Double(register) => {
+                let register = self.register(register);
+                self.arithmetic(&register, &format!("(int64_t){} * 2", register), line)
+            }
+            Instruction::Halve(register) => {
+                let register = self.register(register);
+                if self.settings.division == DivisionBehavior::Floor {
+                    self.helpers.insert("divide");
+                    format!("{0} = divide({0}, 2, {1});", register, line)
+                } else {
+                    format!("{0} = {0} / 2;", register)
+                }
+            }
+            Instruction::ShiftLeft(register, amount) => {
+                self.call_with_operand("shift_left", register, amount, line)
+            }
+            Instruction::ShiftRight(register, amount) => {
+                self.call_with_operand("shift_right", register, amount, line)
+            }
+            Instruction::SquareRoot(register) => {
+                let register = self.register(register);
+                self.helpers.insert("square_root");
+                format!("{0} = square_root({0}, {1});", register, line)
+            }
+            Instruction::Randomize(register, upper_bound) => {
+                let register = self.register(register);
+                self.helpers.insert("random_up_to");
+                format!("{} = random_up_to({});", register, upper_bound)
+            }
+            Instruction::Assign(register, value) => {
+                let register = self.register(register);
+                let value = self.operand(value);
+                format!("{} = {};", register, value)
+            }
+            Instruction::AssignLabelLine(register, target) => {
+                let register = self.register(register);
+                format!("{} = {};", register, target.line_number + 1)
+            }
+            Instruction::Swap(first_register, second_register) => {
+                let first_register = self.register(first_register);
+                let second_register = self.register(second_register);
+                format!(
+                    "{{ int32_t swapped = {0}; {0} = {1}; {1} = swapped; }}",
+                    first_register, second_register
+                )
+            }
+            Instruction::Add(register, other) => {
+                let register = self.register(register);
+                let other = self.operand(other);
+                self.arithmetic(
+                    &register,
+                    &format!("(int64_t){} + {}", register, other),
+                    line,
+                )
+            }
+            Instruction::Subtract(register, other) => {
+                let register = self.register(register);
+                let other = self.operand(other);
+                self.arithmetic(
+                    &register,
+                    &format!("(int64_t){} - {}", register, other),
+                    line,
+                )
+            }
+            Instruction::Min(register, other) => {
+                self.call_with_register("minimum", register, other)
+            }
+            Instruction::Max(register, other) => {
+                self.call_with_register("maximum", register, other)
+            }
+            Instruction::Divide(register, divisor) => {
+                self.call_with_operand("divide", register, divisor, line)
+            }
+            Instruction::Modulo(register, divisor) => {
+                self.call_with_operand("modulo", register, divisor, line)
+            }
+            Instruction::And(register, other) => self.bitwise("&", register, other),
+            Instruction::Or(register, other) => self.bitwise("|", register, other),
+            Instruction::Xor(register, other) => self.bitwise("^", register, other),
+            Instruction::Read(register) => {
+                let register = self.register(register);
+                self.helpers.insert("read_byte");
+                format!("{} = read_byte();", register)
+            }
+            Instruction::ReadWord(register) => {
+                let register = self.register(register);
+                self.helpers.insert("read_word");
+                format!("{} = read_word();", register)
+            }
+            Instruction::ReadLine(register) => {
+                let register = self.register(register);
+                self.helpers.insert("read_line");
+                format!("{} = read_line();", register)
+            }
+            Instruction::ReadInt(register) => {
+                let register = self.register(register);
+                self.helpers.insert("read_int");
+                format!("{} = read_int({});", register, line)
+            }
+            Instruction::ReadAll(register) => {
+                let register = self.register(register);
+                self.helpers.insert("read_all");
+                format!("{} = read_all({});", register, line)
+            }
+            Instruction::Print(register) => self.print(std::slice::from_ref(register), line),
+            Instruction::PrintAll(registers) => self.print(registers, line),
+            Instruction::PrintHex(register) => {
+                let register = self.register(register);
+                self.helpers.insert("print_hex");
+                let flush = if self.settings.line_buffered {
+                    " fflush(stdout);"
+                } else {
+                    ""
+                };
+                format!("print_hex({});{}", register, flush)
+            }
+            Instruction::Halt => {
+                self.halts = true;
+                "goto end;".to_string()
+            }
+            Instruction::Jump(target) => format!("goto {};", label_name(target.line_number)),
+            Instruction::JumpIndirect(register) => self.jump_indirect(register, line),
+            Instruction::Call(target) => self.call(target),
+            Instruction::Return => self.ret(line),
+            Instruction::JumpIfZero(register, target) => {
+                let condition = format!("{} == 0", self.register(register));
+                conditional_jump(&condition, target)
+            }
+            Instruction::JumpIfNotZero(register, target) => {
+                let condition = format!("{} != 0", self.register(register));
+                conditional_jump(&condition, target)
+            }
+            Instruction::JumpIfNegative(register, target) => {
+                let condition = format!("{} < 0", self.register(register));
+                conditional_jump(&condition, target)
+            }
+            Instruction::JumpIfPositive(register, target) => {
+                let condition = format!("{} > 0", self.register(register));
+                conditional_jump(&condition, target)
+            }
+            Instruction::JumpIfEqual(register, value, target) => {
+                let condition = format!("{} == {}", self.register(register), self.operand(value));
+                conditional_jump(&condition, target)
+            }
+            Instruction::JumpIfGreater(first_register, second_register, target) => {
+                let condition = format!(
+                    "{} > {}",
+                    self.register(first_register),
+                    self.register(second_register)
+                );
+                conditional_jump(&condition, target)
+            }
+        };
+        self.body.push_str(&format!("    {}\n", statement));
+    }
+
+    /// Gets the C variable for the register with the provided name, and records that it's used.
+    fn register(&mut self, name: &str) -> String {
+        self.registers.insert(name.to_string());
+        register_variable(name)
+    }
+
+    /// Gets the C expression for the value of an operand.
+    fn operand(&mut self, operand: &Operand) -> String {
+        match operand {
+            Operand::Register(name) => self.register(name),
+            Operand::Literal(value) => literal(*value),
+            Operand::Label(_) => unreachable!("labels are never parsed as values"),
+        }
+    }
+
+    /// Builds a statement that stores the result of a 64-bit arithmetic expression in a register, handling overflow according to the settings.
+    fn arithmetic(&mut self, register: &str, expression: &str, line: usize) -> String {
+        self.helpers.insert("fit");
+        format!("{} = fit({}, {});", register, expression, line)
+    }
+
+    /// Builds a statement that bitwise combines the values in two registers and stores the result in the first one.
+    fn bitwise(&mut self, operator: &str, register: &str, other: &str) -> String {
+        let register = self.register(register);
+        let other = self.register(other);
+        format!("{0} = {0} {1} {2};", register, operator, other)
+    }
+
+    /// Builds a statement that calls a helper with the values in two registers and stores the result in the first one.
+    fn call_with_register(&mut self, helper: &'static str, register: &str, other: &str) -> String {
+        let register = self.register(register);
+        let other = self.register(other);
+        self.helpers.insert(helper);
+        format!("{0} = {1}({0}, {2});", register, helper, other)
+    }
+
+    /// Builds a statement that calls a helper with the value in a register, the value of an operand, and the line number, and stores the result in the register.
+    fn call_with_operand(
+        &mut self,
+        helper: &'static str,
+        register: &str,
+        operand: &Operand,
+        line: usize,
+    ) -> String {
+        let register = self.register(register);
+        let operand = self.operand(operand);
+        self.helpers.insert(helper);
+        format!("{0} = {1}({0}, {2}, {3});", register, helper, operand, line)
+    }
+
+    /// Builds a statement that prints the values in registers, in order.
+    fn print(&mut self, registers: &[String], line: usize) -> String {
+        self.helpers.insert("print_value");
+        let mut statement = registers
+            .iter()
+            .map(|register| format!("print_value({}, {});", self.register(register), line))
+            .collect::<Vec<String>>()
+            .join(" ");
+        if self.settings.line_buffered {
+            statement.push_str(" fflush(stdout);");
+        }
+        statement
+    }
+
+    /// Builds a statement that jumps to the label defined on the 1-indexed line number given by the value in a register.
+    fn jump_indirect(&mut self, register: &str, line: usize) -> String {
+        let register = self.register(register);
+        let label_line_numbers = self.program.label_line_numbers();
+
+        self.helpers.insert("fail");
+        let mut statement = format!("switch ({}) {{\n", register);
+        for line_number in label_line_numbers.iter() {
+            statement.push_str(&format!(
+                "    case {}: goto {};\n",
+                line_number + 1,
+                label_name(*line_number)
+            ));
+        }
+        statement.push_str(&format!(
+            "    default: fail({}, \"no label on line %\" PRId32, {});\n    }}",
+            line, register
+        ));
+        statement
+    }
+
+    /// Builds a statement that jumps to a label, remembering where to return to.
+    fn call(&mut self, target: &JumpTarget) -> String {
+        let return_site = self.calls;
+        self.calls += 1;
+        self.helpers.insert("push_call");
+        let jump = format!(
+            "push_call({}); goto {};",
+            return_site,
+            label_name(target.line_number)
+        );
+        // a return site only needs a label if something can return to it
+        if self.has_return {
+            format!("{} {}:;", jump, return_site_name(return_site))
+        } else {
+            jump
+        }
+    }
+
+    /// Builds a statement that returns to the place the most recent call returns to.
+    fn ret(&mut self, line: usize) -> String {
+        self.helpers.insert("pop_call");
+        let mut statement = format!("switch (pop_call({})) {{\n", line);
+        for return_site in 0..self.call_count {
+            statement.push_str(&format!(
+                "    case {}: goto {};\n",
+                return_site,
+                return_site_name(return_site)
+            ));
+        }
+        statement.push_str("    }");
+        statement
+    }
+
+    /// Assembles the complete C program from the statements emitted so far.
+    fn finish(mut self) -> String {
+        if self.helpers.contains("fit") && self.settings.overflow == OverflowBehavior::Trap {
+            self.helpers.insert("fail");
+        }
+        // helpers come after their dependencies, so going backwards finds every dependency of a helper after the helper itself
+        for helper in HELPERS.iter().rev() {
+            if self.helpers.contains(helper.name) {
+                self.helpers.extend(helper.dependencies.iter());
+            }
+        }
+
+        let mut c = String::new();
+        c.push_str("/* generated by strategic-communication */\n");
+        for header in &[
+            "inttypes.h",
+            "stdarg.h",
+            "stdint.h",
+            "stdio.h",
+            "stdlib.h",
+            "string.h",
+            "time.h",
+        ] {
+            c.push_str(&format!("#include <{}>\n", header));
+        }
+        c.push('\n');
+        c.push_str("/* the value stored when reading from stdin reaches the end of the input */\n");
+        c.push_str(&format!(
+            "#define EOF_VALUE {}\n",
+            literal(self.settings.eof)
+        ));
+        c.push_str(
+            "/* whether division rounds toward negative infinity instead of toward zero */\n",
+        );
+        c.push_str(&format!(
+            "#define FLOOR_DIVISION {}\n\n",
+            (self.settings.division == DivisionBehavior::Floor) as i32
+        ));
+
+        for helper in HELPERS.iter() {
+            if !self.helpers.contains(helper.name) {
+                continue;
+            }
+            if helper.name == "fail" {
+                c.push_str(&fail_source(self.origins));
+            } else if helper.name == "fit" {
+                c.push_str(fit_source(self.settings.overflow));
+            } else if helper.name == "args" {
+                c.push_str(&args_source(&self.settings.args));
+            } else {
+                c.push_str(helper.source);
+            }
+            c.push_str("\n\n");
+        }
+
+        c.push_str("int main(void) {\n");
+        for register in all_register_names(self.settings) {
+            if self.registers.contains(&register) {
+                c.push_str(&format!(
+                    "    int32_t {} = {};\n",
+                    register_variable(&register),
+                    literal(self.initial_registers[&register])
+                ));
+            }
+        }
+        if self.helpers.contains("random_up_to") {
+            let seed = match self.settings.seed {
+                Some(seed) => format!("{}u", seed as u32),
+                None => "(unsigned)time(NULL)".to_string(),
+            };
+            c.push_str(&format!("    srand({});\n", seed));
+        }
+        c.push('\n');
+        c.push_str(&self.body);
+        if self.halts {
+            c.push_str("end:\n");
+        }
+        c.push_str("    fflush(stdout);\n");
+        c.push_str("    return 0;\n");
+        c.push_str("}\n");
+        c
+    }
+}
+
+/// Gets the source code of the `fail` helper, which reports an error on a line and stops the program.
+/// Lines are identified by their index in the program, and a table maps each one to the file and line it was read from.
+fn fail_source(origins: &[Origin]) -> String {
+    let locations: Vec<String> = origins
+        .iter()
+        .map(|origin| format!("    {}", string_literal(&origin.to_string())))
+        .collect();
+    format!(
+        r#"/* the file and line each line of the program was read from */
+static const char *const locations[] = {{
+{}
+}};
+
+static void fail(int line, const char *format, ...) {{
+    va_list args;
+    fflush(stdout);
+    fprintf(stderr, "%s: ", locations[line]);
+    va_start(args, format);
+    vfprintf(stderr, format, args);
+    va_end(args);
+    fputc('\n', stderr);
+    exit(EXIT_FAILURE);
+}}"#,
+        locations.join(",\n")
+    )
+}
+
+/// Gets the source code of the `fit` helper, which converts the 64-bit result of an arithmetic operation to 32 bits.
+fn fit_source(overflow: OverflowBehavior) -> &'static str {
+    match overflow {
+        OverflowBehavior::Wrap => {
+            r#"static int32_t fit(int64_t value, int line) {
+    (void)line;
+    return (int32_t)(uint32_t)(uint64_t)value;
+}"#
+        }
+        OverflowBehavior::Saturate => {
+            r#"static int32_t fit(int64_t value, int line) {
+    (void)line;
+    if (value > INT32_MAX) {
+        return INT32_MAX;
+    }
+    if (value < INT32_MIN) {
+        return INT32_MIN;
+    }
+    return (int32_t)value;
+}"#
+        }
+        OverflowBehavior::Trap => {
+            r#"static int32_t fit(int64_t value, int line) {
+    if (value > INT32_MAX || value < INT32_MIN) {
+        fail(line, "arithmetic overflow");
+    }
+    return (int32_t)value;
+}"#
+        }
+    }
+}
+
+/// Gets the source code of the `args` helper, which provides the integers that are read before anything from stdin.
+fn args_source(args: &[i32]) -> String {
+    if args.is_empty() {
+        return r#"static int pop_arg(int32_t *value) {
+    (void)value;
+    return 0;
+}"#
+        .to_string();
+    }
+
+    let args: Vec<String> = args.iter().map(|arg| literal(*arg)).collect();
+    format!(
+        r#"/* integers that are read before anything from stdin */
+static const int32_t args[] = {{ {} }};
+static size_t next_arg = 0;
+
+static int pop_arg(int32_t *value) {{
+    if (next_arg == sizeof args / sizeof args[0]) {{
+        return 0;
+    }}
+    *value = args[next_arg++];
+    return 1;
+}}"#,
+        args.join(", ")
+    )
+}
+
+/// Builds a statement that jumps to a label if a condition is true.
+fn conditional_jump(condition: &str, target: &JumpTarget) -> String {
+    format!(
+        "if ({}) goto {};",
+        condition,
+        label_name(target.line_number)
+    )
+}
+
+/// Gets the C literal for a value.
+fn literal(value: i32) -> String {
+    if value == i32::MIN {
+        "INT32_MIN".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Gets the C string literal for some text.
+fn string_literal(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for byte in text.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                literal.push('\\');
+                literal.push(byte as char);
+            }
+            // escaped so it can't start a trigraph
+            b'?' => literal.push_str("\\?"),
+            b' '..=b'~' => literal.push(byte as char),
+            _ => literal.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Gets the name of the C variable for a register.
+/// Extra registers get a prefix, so one named with a single word can't clash with a C keyword or a helper.
+fn register_variable(name: &str) -> String {
+    let variable = name.replace(' ', "_");
+    if REGISTER_NAMES.contains(&name) {
+        variable
+    } else {
+        format!("extra_{}", variable)
+    }
+}
+
+/// Gets the name of the C label for the label defined on a line.
+fn label_name(line_number: usize) -> String {
+    format!("label_{}", line_number + 1)
+}
+
+/// Gets the name of the C label for the place a call returns to.
+fn return_site_name(return_site: usize) -> String {
+    format!("return_{}", return_site)
+}
@@ -0,0 +1,116 @@
+use crate::operations::parse_operands;
+use crate::{
+    match_operation, split_lines, ParseOptions, COMMENT_MARKER, COMMENT_PREFIX, LITERAL_CONNECTORS,
+    OPERAND_CONNECTORS,
+};
+
+/// Rewrites the source code of a program with consistent connectors and whitespace.
+/// Each line is trimmed, the operands of each operation are separated by the connectors listed for it in `OPERATIONS`, and runs of blank lines are collapsed into one.
+/// Comments, and the case of everything other than connectors, are kept as they are.
+///
+/// # Arguments
+/// * `source`: The source code to format, which must have already been parsed successfully.
+/// * `options`: Options that affect how operands are parsed.
+/// * `case_sensitive`: Whether the source code is case-sensitive.
+///
+/// Returns the formatted source code, ending with a newline.
+pub fn format(source: &str, options: &ParseOptions, case_sensitive: bool) -> String {
+    let mut formatted = String::new();
+    // starting out as if after a blank line removes any blank lines at the start
+    let mut previous_blank = true;
+    for line in split_lines(source) {
+        let line = format_line(line, options, case_sensitive);
+        let blank = line.is_empty();
+        if !(blank && previous_blank) {
+            formatted.push_str(&line);
+            formatted.push('\n');
+        }
+        previous_blank = blank;
+    }
+    if formatted.ends_with("\n\n") {
+        formatted.pop();
+    }
+    formatted
+}
+
+/// Formats a single line of source code, keeping any comment on it.
+///
+/// # Arguments
+/// * `line`: The line to format.
+/// * `options`: Options that affect how operands are parsed.
+/// * `case_sensitive`: Whether the source code is case-sensitive.
+fn format_line(line: &str, options: &ParseOptions, case_sensitive: bool) -> String {
+    let line = line.trim();
+    // ASCII lowercasing keeps every character in the same place, so positions found in the lowercased line can be used in the original one
+    let lowercased = line.to_ascii_lowercase();
+    if lowercased.starts_with(COMMENT_PREFIX) {
+        return line.to_string();
+    }
+
+    let (code, comment) = match lowercased.find(COMMENT_MARKER) {
+        Some(x) => (line[..x].trim_end(), Some(&line[x..])),
+        None => (line, None),
+    };
+    let code = format_code(code, options, case_sensitive);
+    match comment {
+        Some(comment) if code.is_empty() => comment.to_string(),
+        Some(comment) => format!("{} {}", code, comment),
+        None => code,
+    }
+}
+
+/// Formats the part of a line of source code that isn't a comment.
+/// Lines that aren't operations with operands to connect, like `see attached` lines, are kept as they are.
+///
+/// # Arguments
+/// * `code`: The code to format, trimmed.
+/// * `options`: Options that affect how operands are parsed.
+/// * `case_sensitive`: Whether the source code is case-sensitive.
+fn format_code(code: &str, options: &ParseOptions, case_sensitive: bool) -> String {
+    let matched = if case_sensitive {
+        code.to_string()
+    } else {
+        code.to_ascii_lowercase()
+    };
+    let (operation, operands) = match match_operation(&matched) {
+        Some((operation, operands)) if !operation.connectors.is_empty() => (operation, operands),
+        _ => return code.to_string(),
+    };
+    let parsed = match parse_operands(&operands, options) {
+        Ok(parsed) => parsed,
+        Err(_) => return code.to_string(),
+    };
+
+    // the pattern only removes the operation name from the start of the line, so the operands start right after it
+    let operands_start = matched.len() - operands.len();
+    let mut formatted = code[..operands_start].to_string();
+    for (i, (_, offset)) in parsed.iter().enumerate() {
+        let start = operands_start + offset;
+        let end = match parsed.get(i + 1) {
+            Some((_, next_offset)) => operands_start + next_offset,
+            None => code.len(),
+        };
+        match find_connector(&matched[start..end]) {
+            Some(connector) => {
+                formatted.push_str(&code[start..end - connector.len()]);
+                let index = i.min(operation.connectors.len() - 1);
+                formatted.push_str(operation.connectors[index]);
+            }
+            // operands that weren't separated by a connector are left that way, so they're parsed the same way as before
+            None => formatted.push_str(&code[start..end]),
+        }
+    }
+    formatted
+}
+
+/// Finds the connector at the end of an operand, if there is one.
+///
+/// # Arguments
+/// * `operand`: The operand, followed by anything that was between it and the next operand.
+fn find_connector(operand: &str) -> Option<&'static str> {
+    LITERAL_CONNECTORS
+        .iter()
+        .chain(OPERAND_CONNECTORS.iter())
+        .find(|connector| operand.ends_with(*connector))
+        .copied()
+}
@@ -0,0 +1,153 @@
+use crate::{clean_line, split_lines, Error, Settings, COMMENT_MARKER, INCLUDE_PREFIX};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The name used for source code that wasn't read from a file.
+pub const UNNAMED_SOURCE: &str = "<source>";
+
+/// Where a line of source code was read from.
+#[derive(Debug, Clone)]
+pub struct Origin {
+    /// The path of the file the line was read from.
+    pub file: String,
+    /// The 0-indexed line number of the line in that file, counting blank lines and comments.
+    pub line_number: usize,
+}
+
+impl fmt::Display for Origin {
+    /// Writes the path of the file and the 1-indexed line number, like `file.business:3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line_number + 1)
+    }
+}
+
+/// Lines of source code collected from a file and the files it includes.
+struct Expansion {
+    /// The normalized lines collected so far.
+    lines: Vec<String>,
+    /// Where each of the collected lines was read from.
+    origins: Vec<Origin>,
+    /// The canonical paths of the files currently being included, outermost first.
+    including: Vec<PathBuf>,
+    /// Whether to keep the case of the lines instead of lowercasing them.
+    case_sensitive: bool,
+}
+
+/// Splits source code into lines, normalizes each one, and removes the ones that are empty.
+/// Each `see attached` line is replaced with the lines of the file it names, which can include other files in turn.
+///
+/// # Arguments
+/// * `source`: The source code to expand.
+/// * `name`: The name to say the source code's lines came from, usually the path of its file.
+/// * `settings`: Settings that control how the source code is normalized. Included files are found relative to the directory of `settings.file`,
+///   and including a file is an error if it's not set, so source code that wasn't read from a file never causes other files to be read.
+///
+/// Returns the lines, along with where each one was read from, or `Err(Error)` if an included file can't be read or includes itself.
+pub fn expand(
+    source: &str,
+    name: &str,
+    settings: &Settings,
+) -> Result<(Vec<String>, Vec<Origin>), Error> {
+    let file = settings.file.as_deref().map(Path::new);
+    let mut expansion = Expansion {
+        lines: Vec::new(),
+        origins: Vec::new(),
+        including: file
+            .and_then(|path| fs::canonicalize(path).ok())
+            .into_iter()
+            .collect(),
+        case_sensitive: settings.case_sensitive,
+    };
+    expansion.add_source(source, name, file)?;
+    Ok((expansion.lines, expansion.origins))
+}
+
+impl Expansion {
+    /// Adds the lines of some source code, including any files it names.
+    ///
+    /// # Arguments
+    /// * `source`: The source code to add.
+    /// * `name`: The name to say the source code's lines came from.
+    /// * `file`: The path of the file the source code was read from, or `None` if it wasn't read from a file.
+    fn add_source(&mut self, source: &str, name: &str, file: Option<&Path>) -> Result<(), Error> {
+        for (line_number, line) in split_lines(source).into_iter().enumerate() {
+            let cleaned = clean_line(line, self.case_sensitive);
+            if cleaned.is_empty() {
+                continue;
+            }
+
+            let origin = Origin {
+                file: name.to_string(),
+                line_number,
+            };
+            if cleaned.starts_with(INCLUDE_PREFIX) {
+                // the path is taken from the original line so its case is kept
+                let path = line.trim().get(INCLUDE_PREFIX.len()..).unwrap_or("");
+                let path = match path.find(COMMENT_MARKER) {
+                    Some(x) => path[..x].trim(),
+                    None => path.trim(),
+                };
+                self.include(path, file, origin, line.trim())?;
+            } else {
+                self.lines.push(cleaned);
+                self.origins.push(origin);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the lines of a file named by a `see attached` line.
+    ///
+    /// # Arguments
+    /// * `path`: The path of the file, relative to the directory of the file it's included from.
+    /// * `from`: The path of the file it's included from, or `None` if that source code wasn't read from a file.
+    /// * `origin`: Where the `see attached` line was read from.
+    /// * `line`: The source code of the `see attached` line, used in error messages.
+    fn include(
+        &mut self,
+        path: &str,
+        from: Option<&Path>,
+        origin: Origin,
+        line: &str,
+    ) -> Result<(), Error> {
+        let error = |message: String| Error {
+            line_number: self.lines.len(),
+            column: None,
+            message,
+            source_line: Some(line.to_string()),
+            origin: Some(origin.clone()),
+        };
+        // without a file to be relative to, the path would be relative to wherever the interpreter happens to be running
+        let from = from.ok_or_else(|| {
+            error(format!(
+                "cannot include {} from source code that wasn't read from a file",
+                path
+            ))
+        })?;
+        let path = from.parent().unwrap_or_else(|| Path::new("")).join(path);
+
+        let canonical_path = fs::canonicalize(&path).map_err(|e| {
+            error(format!(
+                "cannot read included file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if self.including.contains(&canonical_path) {
+            return Err(error(format!("circular include of {}", path.display())));
+        }
+        let source = fs::read_to_string(&path).map_err(|e| {
+            error(format!(
+                "cannot read included file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        self.including.push(canonical_path);
+        self.add_source(&source, &path.display().to_string(), Some(&path))?;
+        self.including.pop();
+        Ok(())
+    }
+}
@@ -0,0 +1,2021 @@
+//! Interpreter for the programming language Strategic Communication.
+//! More information can be found at https://github.com/rotoclone/strategic-communication/blob/master/README.md
+
+mod ast;
+mod batch;
+mod cfg;
+mod debugger;
+mod emit_c;
+mod format;
+mod include;
+mod lsp;
+mod operations;
+mod repl;
+
+use clap::ArgEnum;
+use include::{Origin, UNNAMED_SOURCE};
+use operations::{Instruction, Operand};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[macro_use]
+extern crate log;
+
+#[macro_use]
+extern crate lazy_static;
+
+const REGISTER_NAMES: [&str; 8] = [
+    "customer experience",
+    "revenue streams",
+    "core competencies",
+    "best practices",
+    "stakeholder engagement",
+    "key performance indicators",
+    "return on investment",
+    "assets",
+];
+
+/// Strings that can be placed between operands.
+const OPERAND_CONNECTORS: [&str; 3] = [" and ", " with ", " to "];
+
+/// Strings that can be placed between literals.
+const LITERAL_CONNECTORS: [&str; 3] = [", and ", " and ", ", "];
+
+/// String that can be placed before a literal to negate it.
+const NEGATIVE_LITERAL_PREFIX: &str = "deficit ";
+
+/// String that separates the name of a constant from its value in a constant definition.
+const CONSTANT_SEPARATOR: &str = " be ";
+
+/// Lines starting with this string are comments.
+const COMMENT_PREFIX: &str = "fyi,";
+
+/// Everything on a line after this string is a comment.
+const COMMENT_MARKER: &str = "--";
+
+/// Lines starting with this string include the source code of another file.
+const INCLUDE_PREFIX: &str = "see attached ";
+
+/// The number of lines to execute between checks of whether a program has run out of time.
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+
+lazy_static! {
+    /// Map of literals, as they're written in case-sensitive mode, to the values they represent.
+    static ref LITERALS: HashMap<String, u8> = {
+        let mut map = HashMap::new();
+        map.insert("HR".to_string(), 0);
+        map.insert("Engineering".to_string(), 1);
+        map.insert("Legal".to_string(), 2);
+        map.insert("PR".to_string(), 3);
+        map.insert("Finance".to_string(), 4);
+        map.insert("Marketing".to_string(), 5);
+        map.insert("R&D".to_string(), 6);
+        map.insert("Sales".to_string(), 7);
+        map.insert("Manufacturing".to_string(), 8);
+        map.insert("Executive Management".to_string(), 9);
+        map
+    };
+    /// Map of the additional literals that can be used in hexadecimal mode, as they're written in case-sensitive mode, to the values they represent.
+    static ref HEX_LITERALS: HashMap<String, u8> = {
+        let mut map = HashMap::new();
+        map.insert("Operations".to_string(), 10);
+        map.insert("Procurement".to_string(), 11);
+        map.insert("Compliance".to_string(), 12);
+        map.insert("Logistics".to_string(), 13);
+        map.insert("Customer Success".to_string(), 14);
+        map.insert("Facilities".to_string(), 15);
+        map
+    };
+    /// The pattern for lines that define a label.
+    static ref LABEL_PATTERN: Regex = Regex::new("^(moving|going) forward, ").unwrap();
+    /// The pattern for lines that define a constant.
+    static ref CONSTANT_PATTERN: Regex = Regex::new("^let ").unwrap();
+    static ref OPERATIONS: [Operation; 46] = [
+        Operation {
+            name: "label",
+            description: "define a label called x",
+            operands: "x",
+            pattern: LABEL_PATTERN.clone(),
+            parse: operations::label,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "define_constant",
+            description: "define a constant called x with the value of the constant expression y",
+            operands: "x be y",
+            pattern: CONSTANT_PATTERN.clone(),
+            parse: operations::define_constant,
+            connectors: &[],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "increment",
+            description: "increment the value in x",
+            operands: "x",
+            pattern: Regex::new("^(innovate|value-add) ").unwrap(),
+            parse: operations::increment,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "decrement",
+            description: "decrement the value in x",
+            operands: "x",
+            pattern: Regex::new("^(streamline|optimize) ").unwrap(),
+            parse: operations::decrement,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "negate",
+            description: "multiply the value in x by -1",
+            operands: "x",
+            pattern: Regex::new("^(revamp|overhaul) ").unwrap(),
+            parse: operations::negate,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "complement",
+            description: "flip all the bits of the value in x",
+            operands: "x",
+            pattern: Regex::new("^reimagine ").unwrap(),
+            parse: operations::complement,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "double",
+            description: "multiply the value in x by 2",
+            operands: "x",
+            pattern: Regex::new("^(amplify|incentivize) ").unwrap(),
+            parse: operations::double,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "halve",
+            description: "divide the value in x by 2",
+            operands: "x",
+            pattern: Regex::new("^backburner ").unwrap(),
+            parse: operations::halve,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "shift_left",
+            description: "shift the value in x left by y bits, where y is a register or a constant expression",
+            operands: "x with y",
+            pattern: Regex::new("^escalate ").unwrap(),
+            parse: operations::shift_left,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "shift_right",
+            description: "shift the value in x right by y bits, where y is a register or a constant expression",
+            operands: "x with y",
+            pattern: Regex::new("^downsize ").unwrap(),
+            parse: operations::shift_right,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "isqrt",
+            description: "set the value in x to the square root of the value in x",
+            operands: "x",
+            pattern: Regex::new("^rightsize ").unwrap(),
+            parse: operations::isqrt,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "randomize",
+            description: "set the value in x to a random number between 0 and 9 inclusive, or between 0 and the literal y inclusive if it's provided",
+            operands: "x [to y]",
+            pattern: Regex::new("^paradigm shift ").unwrap(),
+            parse: operations::randomize,
+            connectors: &[" to "],
+            min_operands: 1,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "clear",
+            description: "set the value in x to 0",
+            operands: "x",
+            pattern: Regex::new("^sunset ").unwrap(),
+            parse: operations::clear,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "assign",
+            description: "set the value in x to the value of y, or the value in y to the value of x if x is a constant expression",
+            operands: "x with y",
+            pattern: Regex::new("^align ").unwrap(),
+            parse: operations::assign,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "swap",
+            description: "exchange the values in x and y",
+            operands: "x with y",
+            pattern: Regex::new("^(swap|reallocate) ").unwrap(),
+            parse: operations::swap,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "add",
+            description: "add the value in y to the value in x",
+            operands: "x and y",
+            pattern: Regex::new("^(synergize|integrate) ").unwrap(),
+            parse: operations::add,
+            connectors: &[" and "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "subtract",
+            description: "subtract the value in y from the value in x",
+            operands: "x and y",
+            pattern: Regex::new("^differentiate ").unwrap(),
+            parse: operations::subtract,
+            connectors: &[" and "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "add_literal",
+            description: "add the value of the constant expression y to the value in x",
+            operands: "x with y",
+            pattern: Regex::new("^upscale ").unwrap(),
+            parse: operations::add_literal,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "subtract_literal",
+            description: "subtract the value of the constant expression y from the value in x",
+            operands: "x with y",
+            pattern: Regex::new("^scale back ").unwrap(),
+            parse: operations::subtract_literal,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "min",
+            description: "set the value in x to the smaller of the values in x and y",
+            operands: "x and y",
+            pattern: Regex::new("^minimize ").unwrap(),
+            parse: operations::min,
+            connectors: &[" and "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "max",
+            description: "set the value in x to the larger of the values in x and y",
+            operands: "x and y",
+            pattern: Regex::new("^maximize ").unwrap(),
+            parse: operations::max,
+            connectors: &[" and "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "divide",
+            description: "divide the value in x by the value in y",
+            operands: "x with y",
+            pattern: Regex::new("^segment ").unwrap(),
+            parse: operations::divide,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "modulo",
+            description: "set the value in x to the remainder of dividing it by the value in y",
+            operands: "x with y",
+            pattern: Regex::new("^recontextualize ").unwrap(),
+            parse: operations::modulo,
+            connectors: &[" with "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "bitwise_and",
+            description: "set the value in x to the bitwise AND of the values in x and y",
+            operands: "x and y",
+            pattern: Regex::new("^consolidate ").unwrap(),
+            parse: operations::bitwise_and,
+            connectors: &[" and "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "bitwise_or",
+            description: "set the value in x to the bitwise OR of the values in x and y",
+            operands: "x and y",
+            pattern: Regex::new("^diversify ").unwrap(),
+            parse: operations::bitwise_or,
+            connectors: &[" and "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "bitwise_xor",
+            description: "set the value in x to the bitwise XOR of the values in x and y",
+            operands: "x and y",
+            pattern: Regex::new("^disrupt ").unwrap(),
+            parse: operations::bitwise_xor,
+            connectors: &[" and "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "read",
+            description: "read a single byte from stdin and store it in x",
+            operands: "x",
+            pattern: Regex::new("^crowdsource ").unwrap(),
+            parse: operations::read,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "read_word",
+            description: "read two bytes from stdin and store them in x as a signed 16-bit integer",
+            operands: "x",
+            pattern: Regex::new("^deep dive into ").unwrap(),
+            parse: operations::read_word,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "read_line",
+            description: "read a line from stdin, store its first byte in x, and save the rest of the line for later reads",
+            operands: "x",
+            pattern: Regex::new("^brainstorm ").unwrap(),
+            parse: operations::read_line,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "read_int",
+            description: "read a base-10 integer from stdin and store it in x",
+            operands: "x",
+            pattern: Regex::new("^solicit feedback from ").unwrap(),
+            parse: operations::read_int,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "read_all",
+            description: "read everything left in stdin and store the number of bytes read in x",
+            operands: "x",
+            pattern: Regex::new("^audit ").unwrap(),
+            parse: operations::read_all,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "print",
+            description: "print the value in x to stdout as a character",
+            operands: "x",
+            pattern: Regex::new("^(deliver|produce) ").unwrap(),
+            parse: operations::print,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "print_all",
+            description: "print the values in x, y, and so on to stdout as characters, in order",
+            operands: "x and y ...",
+            pattern: Regex::new("^broadcast ").unwrap(),
+            parse: operations::print_all,
+            connectors: &[" and "],
+            min_operands: 1,
+            max_operands: None
+        },
+        Operation {
+            name: "print_hex",
+            description: "print the value in x to stdout as a signed hexadecimal number",
+            operands: "x",
+            pattern: Regex::new("^unpack ").unwrap(),
+            parse: operations::print_hex,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "jump",
+            description: "jump to label x",
+            operands: "x",
+            pattern: Regex::new("^(circle back to|revisit) ").unwrap(),
+            parse: operations::jump,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "jump_indirect",
+            description: "jump to the label defined on line x",
+            operands: "x",
+            pattern: Regex::new("^reprioritize ").unwrap(),
+            parse: operations::jump_indirect,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "call",
+            description: "jump to label x, remembering where to return to",
+            operands: "x",
+            pattern: Regex::new("^delegate to ").unwrap(),
+            parse: operations::call,
+            connectors: &[],
+            min_operands: 1,
+            max_operands: Some(1)
+        },
+        Operation {
+            name: "ret",
+            description: "jump to the line after the most recent call that hasn't been returned from yet",
+            operands: "",
+            pattern: Regex::new("^report back$").unwrap(),
+            parse: operations::ret,
+            connectors: &[],
+            min_operands: 0,
+            max_operands: Some(0)
+        },
+        Operation {
+            name: "noop",
+            description: "do nothing",
+            operands: "",
+            pattern: Regex::new("^touch base$").unwrap(),
+            parse: operations::noop,
+            connectors: &[],
+            min_operands: 0,
+            max_operands: Some(0)
+        },
+        Operation {
+            name: "halt",
+            description: "stop the program",
+            operands: "",
+            pattern: Regex::new("^wrap up$").unwrap(),
+            parse: operations::halt,
+            connectors: &[],
+            min_operands: 0,
+            max_operands: Some(0)
+        },
+        Operation {
+            name: "jump_if_zero",
+            description: "jump to label x if the value in y is zero",
+            operands: "y to x",
+            pattern: Regex::new("^pivot ").unwrap(),
+            parse: operations::jump_if_zero,
+            connectors: &[" to "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "jump_if_not_zero",
+            description: "jump to label x if the value in y is not zero",
+            operands: "y to x",
+            pattern: Regex::new("^iterate ").unwrap(),
+            parse: operations::jump_if_not_zero,
+            connectors: &[" to "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "jump_if_neg",
+            description: "jump to label x if the value in y is negative",
+            operands: "y to x",
+            pattern: Regex::new("^restructure ").unwrap(),
+            parse: operations::jump_if_neg,
+            connectors: &[" to "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "jump_if_pos",
+            description: "jump to label x if the value in y is positive",
+            operands: "y to x",
+            pattern: Regex::new("^capitalize ").unwrap(),
+            parse: operations::jump_if_pos,
+            connectors: &[" to "],
+            min_operands: 2,
+            max_operands: Some(2)
+        },
+        Operation {
+            name: "jump_if_equal",
+            description: "jump to label x if the value in y is equal to the value in z",
+            operands: "y and z to x",
+            pattern: Regex::new("^harmonize ").unwrap(),
+            parse: operations::jump_if_equal,
+            connectors: &[" and ", " to "],
+            min_operands: 3,
+            max_operands: Some(3)
+        },
+        Operation {
+            name: "jump_if_greater",
+            description: "jump to label x if the value in y is greater than the value in z",
+            operands: "y and z to x",
+            pattern: Regex::new("^outperform ").unwrap(),
+            parse: operations::jump_if_greater,
+            connectors: &[" and ", " to "],
+            min_operands: 3,
+            max_operands: Some(3)
+        },
+    ];
+}
+
+/// What to do when the result of an arithmetic operation doesn't fit in a register.
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OverflowBehavior {
+    /// Wrap around to the other end of the range of valid values.
+    Wrap,
+    /// Clamp to the largest or smallest valid value.
+    Saturate,
+    /// Stop the program with an error.
+    Trap,
+}
+
+/// How to round the result of a division that isn't exact.
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq)]
+pub enum DivisionBehavior {
+    /// Round toward zero, so -3 divided by 2 is -1, with a remainder of -1.
+    Trunc,
+    /// Round toward negative infinity, so -3 divided by 2 is -2, with a remainder of 1.
+    Floor,
+}
+
+/// Settings that control how a program is run.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// What to do when the result of an arithmetic operation overflows.
+    pub overflow: OverflowBehavior,
+    /// How to round the result of a division that isn't exact.
+    pub division: DivisionBehavior,
+    /// Whether to write output after every line instead of only when the program reads input or finishes.
+    /// Only applies when output is written to stdout.
+    pub line_buffered: bool,
+    /// The seed for the random number generator, or `None` to seed it from the operating system.
+    pub seed: Option<u64>,
+    /// The value to store when reading from stdin reaches the end of the input.
+    pub eof: i32,
+    /// Integers to store, in order, when reading from stdin, before anything is actually read from it.
+    pub args: Vec<i32>,
+    /// Names of registers programs can use in addition to the standard ones, in the order they're defined in.
+    /// Like the standard registers, they start with a value of 0.
+    pub extra_registers: Vec<String>,
+    /// Values to store in registers before the program starts, as pairs of register names and values.
+    /// Registers that aren't listed start at 0, and if a register is listed more than once, the last value is used.
+    pub initial_registers: Vec<(String, i32)>,
+    /// The bytes for `run_source` to read input from instead of stdin, or `None` to read from stdin.
+    pub input: Option<Vec<u8>>,
+    /// Whether to pause before each line and wait for debugger commands, which are read from the same place as the program's input.
+    pub debug: bool,
+    /// Labels to pause in the debugger after, whether or not `debug` is set.
+    /// Not used in interactive mode.
+    pub breakpoints: Vec<String>,
+    /// The maximum number of lines to execute before stopping the program with an error, or `None` for no limit.
+    pub max_steps: Option<u64>,
+    /// How long the program can run before it's stopped with an error, or `None` for no limit.
+    pub timeout: Option<Duration>,
+    /// Whether to write the line number, source code, and resulting register values of each executed line to stderr.
+    pub trace: bool,
+    /// Whether to write how many times each line was executed to stderr when the program finishes.
+    pub profile: bool,
+    /// Whether to write the number of lines executed, jumps taken, bytes read, and bytes printed to stderr when the program finishes.
+    pub stats: bool,
+    /// The path of a CSV file to write the value in every register to after each line is executed, or `None` to not write one.
+    /// Not used in interactive mode.
+    pub record: Option<String>,
+    /// Whether to write warnings about labels that are never jumped to and lines that can never be executed to stderr before running the program.
+    pub warn_unreachable: bool,
+    /// Whether literals are written in base 16 instead of base 10.
+    pub hex: bool,
+    /// The path of the file the source code was read from, or `None` if it wasn't read from a file.
+    /// Files included with `see attached` are found relative to its directory, and including a file is an error if it's `None`.
+    pub file: Option<String>,
+    /// Whether to keep the case of source code instead of lowercasing it, so labels and constants that differ only by case are different,
+    /// and operations, registers, and literals must be written in the case they're documented in.
+    pub case_sensitive: bool,
+    /// Whether labels can only be used as operands of jumps, so `align` can't set a register to the line number of a label.
+    /// This makes a misspelled register in an `align` an error about an unknown register instead of an unknown label.
+    pub strict: bool,
+}
+
+/// Options that affect how source code is parsed.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// The base that literals are written in, either 10 or 16.
+    radix: u32,
+    /// The literals that can be used, as they're written in source code, along with the values they represent.
+    /// Longer literals come first, so a literal that starts with another one is matched in full.
+    literals: Vec<(String, u8)>,
+    /// The names of the registers that can be used.
+    /// Longer names come first, so a register name that starts with another one is matched in full.
+    registers: Vec<String>,
+    /// Map of the names of constants defined in the program to their values.
+    constants: HashMap<String, i32>,
+    /// Whether labels can only be used as operands of jumps.
+    strict: bool,
+}
+
+impl ParseOptions {
+    /// Builds the parse options to use for a program run with the provided settings.
+    ///
+    /// Returns `Err(Error)` if any of the extra registers in the settings can't be used.
+    fn from_settings(settings: &Settings) -> Result<ParseOptions, Error> {
+        let hex_literals = if settings.hex {
+            Some(HEX_LITERALS.iter())
+        } else {
+            None
+        };
+        let mut literals: Vec<(String, u8)> = LITERALS
+            .iter()
+            .chain(hex_literals.into_iter().flatten())
+            .map(|(name, value)| {
+                let name = if settings.case_sensitive {
+                    name.clone()
+                } else {
+                    name.to_lowercase()
+                };
+                (name, *value)
+            })
+            .collect();
+        literals.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+        let mut options = ParseOptions {
+            radix: if settings.hex { 16 } else { 10 },
+            literals,
+            registers: REGISTER_NAMES.iter().map(|name| name.to_string()).collect(),
+            constants: HashMap::new(),
+            strict: settings.strict,
+        };
+        let register_names = all_register_names(settings);
+        for (i, name) in register_names.iter().enumerate().skip(REGISTER_NAMES.len()) {
+            options.check_register_name(name, &register_names[..i])?;
+        }
+        options.registers = register_names;
+        options
+            .registers
+            .sort_by_key(|name| std::cmp::Reverse(name.len()));
+        Ok(options)
+    }
+
+    /// Checks that an extra register name can be used alongside the registers that are already known.
+    ///
+    /// # Arguments
+    /// * `name`: The register name, as it's written in source code.
+    /// * `earlier_names`: The names of the registers defined before this one.
+    fn check_register_name(&self, name: &str, earlier_names: &[String]) -> Result<(), Error> {
+        if earlier_names.iter().any(|earlier| earlier == name) {
+            return Err(Error::new(&format!("duplicate register name: {}", name), 0));
+        }
+        // register names are used in the names of C variables when emitting C
+        let valid_characters =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == ' ');
+        // a name that starts with a standard register or a literal would be parsed as that instead,
+        // and a name that contains a connector would be split into separate operands
+        let parsed_as_itself = match operations::parse_operands(name, self) {
+            Ok(operands) => {
+                matches!(operands.as_slice(), [(Operand::Label(label), _)] if label == name)
+            }
+            Err(_) => false,
+        };
+        let has_connector = OPERAND_CONNECTORS
+            .iter()
+            .any(|connector| name.contains(connector));
+        if !valid_characters || !parsed_as_itself || has_connector {
+            return Err(Error::new(&format!("invalid register name: {}", name), 0));
+        }
+        Ok(())
+    }
+}
+
+/// Gets the names of the registers programs run with the provided settings can use, in the order they're defined in:
+/// the standard registers, then `settings.extra_registers`, lowercased unless case-sensitive mode is on.
+fn all_register_names(settings: &Settings) -> Vec<String> {
+    REGISTER_NAMES
+        .iter()
+        .map(|name| name.to_string())
+        .chain(
+            settings
+                .extra_registers
+                .iter()
+                .map(|name| clean_line(name, settings.case_sensitive)),
+        )
+        .collect()
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            overflow: OverflowBehavior::Wrap,
+            division: DivisionBehavior::Trunc,
+            line_buffered: false,
+            seed: None,
+            eof: -1,
+            args: Vec::new(),
+            extra_registers: Vec::new(),
+            initial_registers: Vec::new(),
+            input: None,
+            debug: false,
+            breakpoints: Vec::new(),
+            max_steps: None,
+            timeout: None,
+            trace: false,
+            profile: false,
+            stats: false,
+            record: None,
+            warn_unreachable: false,
+            hex: false,
+            file: None,
+            case_sensitive: false,
+            strict: false,
+        }
+    }
+}
+
+/// Normalizes a line of source code by trimming it, lowercasing it unless case-sensitive mode is on, and removing any comment.
+///
+/// # Arguments
+/// * `line`: The line to normalize.
+/// * `case_sensitive`: Whether to keep the case of the line instead of lowercasing it.
+fn clean_line(line: &str, case_sensitive: bool) -> String {
+    let line = line.trim();
+    if case_sensitive {
+        // comments are recognized in any case, and ASCII lowercasing keeps every character in the same place
+        let code_length = strip_comment(&line.to_ascii_lowercase()).len();
+        line[..code_length].to_string()
+    } else {
+        strip_comment(&line.to_lowercase()).to_string()
+    }
+}
+
+/// Splits source code into lines, which can end with `\r\n`, `\r`, or `\n`, so files written on any platform have the same lines.
+///
+/// # Arguments
+/// * `source`: The source code to split.
+fn split_lines(source: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut rest = source;
+    while let Some(end) = rest.find(['\r', '\n']) {
+        lines.push(&rest[..end]);
+        let separator_length = if rest[end..].starts_with("\r\n") {
+            2
+        } else {
+            1
+        };
+        rest = &rest[end + separator_length..];
+    }
+    lines.push(rest);
+    lines
+}
+
+/// Removes any comment from a line of source code.
+///
+/// # Arguments
+/// * `line`: The line to remove the comment from, trimmed and lowercased.
+///
+/// Returns the part of the line that isn't a comment, trimmed.
+fn strip_comment(line: &str) -> &str {
+    if line.starts_with(COMMENT_PREFIX) {
+        return "";
+    }
+    match line.find(COMMENT_MARKER) {
+        Some(x) => line[..x].trim_end(),
+        None => line,
+    }
+}
+
+/// Runs a program, reading input from stdin, or from `settings.input` if it's set, and writing output to stdout.
+///
+/// # Arguments
+/// * `name`: The name to use for the program in errors and warnings, usually the path of its file.
+/// * `source`: The source code of the program to run.
+/// * `settings`: Settings that control how the program is run.
+///
+/// Returns a map of register names to their final values, or `Err(Error)` if the program could not be parsed or any errors occurred during its execution.
+pub fn run_source(
+    name: &str,
+    source: &str,
+    settings: Settings,
+) -> Result<HashMap<String, i32>, Error> {
+    let input: Box<dyn BufRead> = match &settings.input {
+        Some(bytes) => Box::new(Cursor::new(bytes.clone())),
+        None => Box::new(BufReader::new(std::io::stdin())),
+    };
+    // stdout is already line buffered on its own
+    let output: Box<dyn Write> = if settings.line_buffered {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(BufWriter::new(std::io::stdout()))
+    };
+    let (program, origins) = load_named_program(source, name, &settings)?;
+    run_program(program, origins, settings, input, output)
+}
+
+/// Runs an interactive session that reads lines of source code from stdin and executes each one as it's entered.
+///
+/// # Arguments
+/// * `settings`: Settings that control how the lines are run.
+///
+/// Returns `Err(Error)` if reading from stdin or writing to stdout fails.
+pub fn run_repl(settings: Settings) -> Result<(), Error> {
+    let input = Box::new(BufReader::new(std::io::stdin()));
+    let output = Box::new(std::io::stdout());
+    repl::run(settings, input, output)
+}
+
+/// Runs every program in a directory that has a `.expected` file next to it, and writes whether each one's output matched that file to stdout.
+///
+/// # Arguments
+/// * `dir`: The path of the directory containing the programs.
+/// * `settings`: Settings that control how the programs are run. `record` is ignored, since every program would write to the same file.
+///
+/// Returns whether every program passed, or `Err(Error)` if the directory or any of the files in it can't be read, or writing to stdout fails.
+pub fn run_batch(dir: &str, settings: Settings) -> Result<bool, Error> {
+    batch::run(dir, &settings, &mut std::io::stdout())
+}
+
+/// Runs a language server that reports errors in programs to an editor, communicating over stdin and stdout with the Language Server Protocol.
+///
+/// # Arguments
+/// * `settings`: Settings that control how programs are parsed.
+///
+/// Returns `Err(Error)` if reading from stdin or writing to stdout fails.
+pub fn run_language_server(settings: Settings) -> Result<(), Error> {
+    let input = Box::new(BufReader::new(std::io::stdin()));
+    let output = Box::new(std::io::stdout());
+    lsp::run(settings, input, output)
+}
+
+/// Runs a program with the provided input and output.
+///
+/// # Arguments
+/// * `source`: The source code of the program to run.
+/// * `settings`: Settings that control how the program is run.
+/// * `input`: Where the program should read input from.
+/// * `output`: Where the program should write output to.
+///
+/// Returns `Err(Error)` if the program could not be parsed or any errors occurred during its execution.
+pub fn run_source_with_io<'a>(
+    source: &str,
+    settings: Settings,
+    input: Box<dyn BufRead + 'a>,
+    output: Box<dyn Write + 'a>,
+) -> Result<(), Error> {
+    run_source_with_state(source, settings, input, output).map(|_| ())
+}
+
+/// Runs a program with the provided input and output, and returns the values in its registers when it finishes.
+///
+/// # Arguments
+/// * `source`: The source code of the program to run.
+/// * `settings`: Settings that control how the program is run.
+/// * `input`: Where the program should read input from.
+/// * `output`: Where the program should write output to.
+///
+/// Returns a map of register names to their final values, or `Err(Error)` if the program could not be parsed or any errors occurred during its execution.
+pub fn run_source_with_state<'a>(
+    source: &str,
+    settings: Settings,
+    input: Box<dyn BufRead + 'a>,
+    output: Box<dyn Write + 'a>,
+) -> Result<HashMap<String, i32>, Error> {
+    let (program, origins) = load_program(source, &settings)?;
+    run_program(program, origins, settings, input, output)
+}
+
+/// Runs a program that's already been parsed.
+///
+/// # Arguments
+/// * `program`: The program to run.
+/// * `origins`: Where each line of the program was read from.
+/// * `settings`: Settings that control how the program is run.
+/// * `input`: Where the program should read input from.
+/// * `output`: Where the program should write output to.
+///
+/// Returns a map of register names to their final values, or `Err(Error)` if any errors occurred during its execution.
+fn run_program<'a>(
+    program: Program,
+    origins: Vec<Origin>,
+    settings: Settings,
+    input: Box<dyn BufRead + 'a>,
+    output: Box<dyn Write + 'a>,
+) -> Result<HashMap<String, i32>, Error> {
+    debug!("parsed program: {:?}", program);
+    if settings.warn_unreachable {
+        for (line_number, warning) in program.find_unreachable() {
+            eprintln!("{}: warning: {}", origins[line_number], warning);
+        }
+    }
+    let registers = initial_registers(&settings)?;
+    let program = Rc::new(program);
+    let mut context = Context::new(Rc::clone(&program), &settings, input, output);
+    context.origins = origins.clone();
+    context.registers = registers;
+    if let Some(path) = &settings.record {
+        context.start_recording(path)?;
+    }
+    debug!("created context: {:?}", context);
+    let mut result = Ok(());
+    while context.current_line_number < program.instructions.len() {
+        result = context
+            .execute_current_line()
+            .map_err(|e| e.with_source(&program.source).with_origins(&origins));
+        if result.is_err() {
+            break;
+        }
+    }
+    // output produced before an error should still be written
+    context.flush_output()?;
+    context.flush_record()?;
+    if settings.profile {
+        context.print_profile();
+    }
+    if settings.stats {
+        context.print_stats();
+    }
+    result.map(|_| context.registers)
+}
+
+/// Converts a program into the source code of an equivalent C program, which can be compiled instead of running the program with the interpreter.
+///
+/// # Arguments
+/// * `source`: The source code of the program to convert.
+/// * `settings`: Settings that control how the program is run. Settings that only affect the interpreter, like `debug` and `trace`, are ignored.
+///
+/// Returns the C source code, or `Err(Error)` if the program could not be parsed.
+pub fn emit_c(source: &str, settings: Settings) -> Result<String, Error> {
+    let (program, origins) = load_program(source, &settings)?;
+    let registers = initial_registers(&settings)?;
+    Ok(emit_c::emit(&program, &settings, &registers, &origins))
+}
+
+/// Converts a program into JSON describing the operation and operands each line was parsed into, and the lines its labels are defined on.
+///
+/// # Arguments
+/// * `source`: The source code of the program to convert.
+/// * `name`: The name to include in the JSON, usually the path of the source file.
+/// * `settings`: Settings that control how the program is parsed.
+///
+/// Returns the JSON, or `Err(Error)` if the program could not be parsed.
+pub fn emit_ast(source: &str, name: &str, settings: Settings) -> Result<String, Error> {
+    let (program, _) = load_named_program(source, name, &settings)?;
+    Ok(ast::to_json(&program, name))
+}
+
+/// Converts a program into a Graphviz DOT graph of the blocks of lines it's made of and the jumps between them.
+///
+/// # Arguments
+/// * `source`: The source code of the program to convert.
+/// * `settings`: Settings that control how the program is parsed.
+///
+/// Returns the DOT source code, or `Err(Error)` if the program could not be parsed.
+pub fn emit_cfg(source: &str, settings: Settings) -> Result<String, Error> {
+    let (program, origins) = load_program(source, &settings)?;
+    Ok(cfg::to_dot(&program, &origins))
+}
+
+/// Rewrites the source code of a program so every operation uses the same connectors between its operands, with consistent whitespace.
+///
+/// # Arguments
+/// * `source`: The source code of the program to format.
+/// * `settings`: Settings that control how the program is parsed.
+///
+/// Returns the formatted source code, or `Err(Error)` if the program could not be parsed.
+pub fn format_source(source: &str, settings: Settings) -> Result<String, Error> {
+    let (program, _) = load_program(source, &settings)?;
+    let options = ParseOptions {
+        constants: program.constants,
+        ..ParseOptions::from_settings(&settings)?
+    };
+    Ok(format::format(source, &options, settings.case_sensitive))
+}
+
+/// Parses a program without running it, so the labels and constants it defines can be looked at.
+///
+/// # Arguments
+/// * `source`: The source code of the program to parse.
+/// * `settings`: Settings that control how the program is parsed.
+///
+/// Returns the program, or `Err(Error)` if the program could not be parsed.
+pub fn parse(source: &str, settings: Settings) -> Result<Program, Error> {
+    load_program(source, &settings).map(|(program, _)| program)
+}
+
+/// Gets the names of the standard registers programs can use, in the order they're listed in the documentation.
+/// Registers added with `Settings::extra_registers` aren't included.
+pub fn register_names() -> &'static [&'static str] {
+    &REGISTER_NAMES
+}
+
+/// Gets the literals programs run with the provided settings can use, as they're documented, along with the values they represent.
+///
+/// # Arguments
+/// * `settings`: Settings that control which literals can be used.
+///
+/// Returns the literals in order of their values.
+pub fn literals(settings: &Settings) -> Vec<(&'static str, u8)> {
+    let hex_literals = if settings.hex {
+        Some(HEX_LITERALS.iter())
+    } else {
+        None
+    };
+    let mut literals: Vec<(&'static str, u8)> = LITERALS
+        .iter()
+        .chain(hex_literals.into_iter().flatten())
+        .map(|(name, value)| (name.as_str(), *value))
+        .collect();
+    literals.sort_by_key(|(_, value)| *value);
+    literals
+}
+
+/// Gets every way of writing the start of an operation, like `innovate` or `moving forward,`, in the order operations are matched in.
+pub fn operation_keywords() -> Vec<String> {
+    OPERATIONS
+        .iter()
+        .flat_map(|operation| operation.keywords())
+        .collect()
+}
+
+/// A description of an operation, for tools like documentation generators that need to know which operations exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationInfo {
+    /// The name of the operation, like `increment`.
+    pub name: &'static str,
+    /// A short description of what the operation does.
+    pub description: &'static str,
+    /// How the operands of the operation are written after its keyword, with letters standing in for them, like `x with y`.
+    pub operands: &'static str,
+    /// Every way of writing the start of the operation, like `innovate` and `value-add`.
+    pub keywords: Vec<String>,
+    /// The fewest operands the operation takes.
+    pub min_operands: usize,
+    /// The most operands the operation takes, or `None` if there's no limit.
+    pub max_operands: Option<usize>,
+}
+
+/// Gets a description of every operation, in the order operations are matched in.
+pub fn operation_table() -> Vec<OperationInfo> {
+    OPERATIONS
+        .iter()
+        .map(|operation| OperationInfo {
+            name: operation.name,
+            description: operation.description,
+            operands: operation.operands,
+            keywords: operation.keywords(),
+            min_operands: operation.min_operands,
+            max_operands: operation.max_operands,
+        })
+        .collect()
+}
+
+/// Builds a reference for the language, listing every operation, register, and literal that programs run with the provided settings can use.
+///
+/// # Arguments
+/// * `settings`: Settings that control which registers and literals can be used.
+///
+/// Returns the reference as text, ending with a newline.
+pub fn language_reference(settings: &Settings) -> String {
+    let mut reference = String::new();
+    reference.push_str("OPERATIONS\n");
+    reference.push_str("Operands are register names unless the description says otherwise.\n");
+    for operation in operation_table() {
+        reference.push('\n');
+        for keyword in &operation.keywords {
+            if operation.operands.is_empty() {
+                reference.push_str(&format!("    {}\n", keyword));
+            } else {
+                reference.push_str(&format!("    {} {}\n", keyword, operation.operands));
+            }
+        }
+        reference.push_str(&format!("        {}\n", operation.description));
+    }
+
+    reference.push_str("\nREGISTERS\n");
+    for name in all_register_names(settings) {
+        reference.push_str(&format!("    {}\n", name));
+    }
+
+    reference.push_str("\nLITERALS\n");
+    reference.push_str(&format!(
+        "Literals are combined into constant expressions by separating them with \",\" or \"and\", and made negative by starting with \"{}\".\n",
+        NEGATIVE_LITERAL_PREFIX.trim()
+    ));
+    for (name, value) in literals(settings) {
+        reference.push_str(&format!("    {} = {}\n", name, value));
+    }
+    reference
+}
+
+/// Parses the source code of a program, along with any files it includes, naming it after `settings.file` if it's set.
+///
+/// # Arguments
+/// * `source`: The source code of the program.
+/// * `settings`: Settings that control how the program is parsed.
+///
+/// Returns the program and where each of its lines was read from, or `Err(Error)` if the program has no lines or could not be parsed.
+fn load_program(source: &str, settings: &Settings) -> Result<(Program, Vec<Origin>), Error> {
+    let name = settings.file.as_deref().unwrap_or(UNNAMED_SOURCE);
+    load_named_program(source, name, settings)
+}
+
+/// Parses the source code of a program, along with any files it includes.
+///
+/// # Arguments
+/// * `source`: The source code of the program.
+/// * `name`: The name to say the program's lines came from, usually the path of its file.
+/// * `settings`: Settings that control how the program is parsed.
+///
+/// Returns the program and where each of its lines was read from, or `Err(Error)` if the program has no lines or could not be parsed.
+fn load_named_program(
+    source: &str,
+    name: &str,
+    settings: &Settings,
+) -> Result<(Program, Vec<Origin>), Error> {
+    let (program, origins) =
+        check_program(source, name, settings).map_err(|mut errors| errors.remove(0))?;
+    // a program made entirely of blank lines and comments is most likely the wrong file
+    if program.instructions.is_empty() {
+        return Err(Error::new("program contains no operations", 0));
+    }
+    Ok((program, origins))
+}
+
+/// Parses the source code of a program, along with any files it includes, finding every error in it instead of stopping at the first one.
+/// Unlike `load_program`, a program with no lines isn't an error.
+///
+/// # Arguments
+/// * `source`: The source code of the program.
+/// * `name`: The name to say the program's lines came from, usually the path of its file.
+/// * `settings`: Settings that control how the program is parsed.
+///
+/// Returns the program and where each of its lines was read from, or `Err(Vec<Error>)` if the program could not be parsed.
+/// An included file that can't be read stops the parsing, so it's the only error reported.
+fn check_program(
+    source: &str,
+    name: &str,
+    settings: &Settings,
+) -> Result<(Program, Vec<Origin>), Vec<Error>> {
+    let (source, origins) = include::expand(source, name, settings).map_err(|e| vec![e])?;
+    let options = ParseOptions::from_settings(settings).map_err(|e| vec![e])?;
+    let program = Program::new_reporting_all_errors(source, &options).map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|e| e.with_origins(&origins))
+            .collect::<Vec<Error>>()
+    })?;
+    Ok((program, origins))
+}
+
+/// Finds the value every register should start with, which is 0 unless a different value is provided in `settings.initial_registers`.
+///
+/// # Arguments
+/// * `settings`: Settings that control how the program is run.
+///
+/// Returns a map of register names to their initial values, or `Err(Error)` if a value is provided for a register that doesn't exist.
+fn initial_registers(settings: &Settings) -> Result<HashMap<String, i32>, Error> {
+    let mut registers: HashMap<String, i32> = all_register_names(settings)
+        .into_iter()
+        .map(|name| (name, 0))
+        .collect();
+    for (name, value) in &settings.initial_registers {
+        let name = clean_line(name, settings.case_sensitive);
+        match registers.get_mut(&name) {
+            Some(register) => *register = *value,
+            None => return Err(Error::new(&format!("unknown register: {}", name), 0)),
+        }
+    }
+    Ok(registers)
+}
+
+/// An error during the parsing or execution of a program.
+#[derive(Debug)]
+pub struct Error {
+    /// The 0-indexed line number the error occurred on.
+    line_number: usize,
+    /// The 0-indexed column on the line the error occurred at, if it's known.
+    column: Option<usize>,
+    /// A message describing the error.
+    message: String,
+    /// The source code of the line the error occurred on, if it's known.
+    source_line: Option<String>,
+    /// Where the line the error occurred on was read from, if it's known.
+    origin: Option<Origin>,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.origin {
+            Some(origin) => {
+                write!(f, "{}", origin)?;
+                if let Some(column) = self.column {
+                    write!(f, ":{}", column + 1)?;
+                }
+            }
+            None => {
+                write!(f, "error on line {}", self.line_number + 1)?;
+                if let Some(column) = self.column {
+                    write!(f, ", col {}", column + 1)?;
+                }
+            }
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(source_line) = &self.source_line {
+            write!(f, "\n    {}", source_line)?;
+            if let Some(column) = self.column {
+                write!(f, "\n    {}^", " ".repeat(column))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error {
+    /// Creates a new `Error` with the provided message.
+    ///
+    /// # Arguments
+    /// * `message`: A message describing the error.
+    /// * `line_number`: The 0-indexed line number the error occurred on.
+    fn new(message: &str, line_number: usize) -> Error {
+        Error {
+            line_number,
+            column: None,
+            message: message.to_string(),
+            source_line: None,
+            origin: None,
+        }
+    }
+
+    /// Creates a new `Error` with the provided message at a specific column.
+    ///
+    /// # Arguments
+    /// * `message`: A message describing the error.
+    /// * `line_number`: The 0-indexed line number the error occurred on.
+    /// * `column`: The 0-indexed column on the line the error occurred at.
+    fn with_column(message: &str, line_number: usize, column: usize) -> Error {
+        Error {
+            line_number,
+            column: Some(column),
+            message: message.to_string(),
+            source_line: None,
+            origin: None,
+        }
+    }
+
+    /// Attaches the source code of the line the error occurred on, so it can be shown with the error.
+    ///
+    /// # Arguments
+    /// * `source`: The source code of the program the error occurred in, split by line.
+    fn with_source(mut self, source: &[String]) -> Error {
+        self.source_line = source.get(self.line_number).cloned();
+        self
+    }
+
+    /// Attaches where the line the error occurred on was read from, so it can be shown with the error.
+    /// If the error already knows where its line was read from, that's kept.
+    ///
+    /// # Arguments
+    /// * `origins`: Where each line of the program the error occurred in was read from.
+    fn with_origins(mut self, origins: &[Origin]) -> Error {
+        if self.origin.is_none() {
+            self.origin = origins.get(self.line_number).cloned();
+        }
+        self
+    }
+}
+
+/// Return type for operation execution functions.
+type OpResult = Result<(), Error>;
+
+/// Return type for operation parsing functions.
+type ParseResult = Result<Instruction, operations::ParseError>;
+
+/// An operation corresponding to a line of source code.
+struct Operation {
+    /// The name of this operation, which is the same as the name of the function that parses it.
+    name: &'static str,
+    /// A short description of what this operation does.
+    description: &'static str,
+    /// How the operands of this operation are written, with letters standing in for them, like `x with y`.
+    operands: &'static str,
+    /// The regular expression to use to determine if a given line corresponds to this operation.
+    pattern: Regex,
+    /// The function that parses the operands of this operation into an `Instruction`.
+    parse: fn(&str, &ParseOptions) -> ParseResult,
+    /// The connectors written between the operands of this operation when it's formatted, in order.
+    /// The last one is also used between any operands after those.
+    connectors: &'static [&'static str],
+    /// The fewest operands this operation takes.
+    min_operands: usize,
+    /// The most operands this operation takes, or `None` if there's no limit.
+    max_operands: Option<usize>,
+}
+
+impl Operation {
+    /// Gets every way of writing the start of this operation, like `innovate` and `value-add`.
+    fn keywords(&self) -> Vec<String> {
+        // every pattern is a list of alternatives in parentheses, or a single keyword, with text before or after it that every alternative shares
+        let pattern = self.pattern.as_str().trim_start_matches('^');
+        let pattern = pattern.trim_end_matches('$').trim_end();
+        let (prefix, alternatives, suffix) = match (pattern.find('('), pattern.find(')')) {
+            (Some(start), Some(end)) => (
+                &pattern[..start],
+                &pattern[start + 1..end],
+                &pattern[end + 1..],
+            ),
+            _ => ("", pattern, ""),
+        };
+        alternatives
+            .split('|')
+            .map(|alternative| format!("{}{}{}", prefix, alternative, suffix))
+            .collect()
+    }
+}
+
+/// Finds the operation a line of source code corresponds to.
+/// If more than one operation matches, the one that comes first in `OPERATIONS` is used.
+///
+/// # Arguments
+/// * `line`: The line to match.
+///
+/// Returns the operation and the operands that follow its name, or `None` if the line doesn't correspond to any operation.
+fn match_operation(line: &str) -> Option<(&'static Operation, String)> {
+    OPERATIONS
+        .iter()
+        .find(|op| op.pattern.is_match(line))
+        .map(|op| (op, op.pattern.replace(line, "").to_string()))
+}
+
+/// A parsed program.
+#[derive(Debug)]
+pub struct Program {
+    /// The source code of the program, split by line.
+    source: Vec<String>,
+    /// The instructions corresponding to each line of the program.
+    instructions: Vec<Instruction>,
+    /// Map of label names to the lines they are defined on.
+    labels: HashMap<String, usize>,
+    /// The lines labels are defined on, in order, so indirect jumps don't have to find them every time.
+    label_line_numbers: Vec<usize>,
+    /// Map of the names of constants defined in the program to their values.
+    constants: HashMap<String, i32>,
+}
+
+impl Program {
+    /// Parses a program.
+    ///
+    /// # Arguments
+    /// * `source`: The source code of the program, split by line.
+    /// * `options`: Options that affect how the source code is parsed.
+    ///
+    /// Returns `Err(Error)` if any line of the program could not be parsed or refers to an unknown label.
+    fn new(source: Vec<String>, options: &ParseOptions) -> Result<Program, Error> {
+        Program::new_reporting_all_errors(source, options).map_err(|mut errors| errors.remove(0))
+    }
+
+    /// Parses a program, finding every error in it instead of stopping at the first one.
+    ///
+    /// # Arguments
+    /// * `source`: The source code of the program, split by line.
+    /// * `options`: Options that affect how the source code is parsed.
+    ///
+    /// Returns `Err(Vec<Error>)` if any line of the program could not be parsed or refers to an unknown label.
+    /// Errors in constant definitions come first, then the other errors, each in line order.
+    fn new_reporting_all_errors(
+        source: Vec<String>,
+        options: &ParseOptions,
+    ) -> Result<Program, Vec<Error>> {
+        let mut errors = Vec::new();
+        // constants can be used before the line they're defined on, so they need to be found first
+        let options = ParseOptions {
+            constants: Program::find_constants(&source, options, &mut errors),
+            ..options.clone()
+        };
+        let mut instructions = Vec::with_capacity(source.len());
+        for (line_number, line) in source.iter().enumerate() {
+            match Program::parse_line(line, line_number, &options) {
+                Ok(instruction) => instructions.push(instruction),
+                Err(e) => {
+                    // the line is replaced so the lines after it keep their line numbers
+                    errors.push(e);
+                    instructions.push(Instruction::Noop);
+                }
+            }
+        }
+        let labels = Program::find_labels(&instructions);
+        Program::resolve_jump_targets(&mut instructions, &labels, &mut errors);
+        if !errors.is_empty() {
+            return Err(errors.into_iter().map(|e| e.with_source(&source)).collect());
+        }
+        let mut label_line_numbers: Vec<usize> = labels.values().copied().collect();
+        label_line_numbers.sort_unstable();
+        Ok(Program {
+            source,
+            instructions,
+            labels,
+            label_line_numbers,
+            constants: options.constants,
+        })
+    }
+
+    /// Finds the constants defined in a program.
+    ///
+    /// # Arguments
+    /// * `source`: The source code of the program, split by line.
+    /// * `options`: Options that affect how the values of the constants are parsed.
+    /// * `errors`: Where to add an error for each constant definition that's invalid or defines a constant that's already defined.
+    ///
+    /// Returns a map of constant names to their values.
+    fn find_constants(
+        source: &[String],
+        options: &ParseOptions,
+        errors: &mut Vec<Error>,
+    ) -> HashMap<String, i32> {
+        let mut constants = HashMap::new();
+        for (line_number, line) in source.iter().enumerate() {
+            if !CONSTANT_PATTERN.is_match(line) {
+                continue;
+            }
+            let operands = CONSTANT_PATTERN.replace(line, "");
+            let operands_start = line.len() - operands.len();
+            let (name, value) = match operations::parse_constant(&operands, options) {
+                Ok(x) => x,
+                Err(e) => {
+                    errors.push(e.into_error(line_number, operands_start));
+                    continue;
+                }
+            };
+            if constants.contains_key(&name) {
+                errors.push(Error::with_column(
+                    &format!("constant already defined: {}", name),
+                    line_number,
+                    operands_start,
+                ));
+                continue;
+            }
+            constants.insert(name, value);
+        }
+        constants
+    }
+
+    /// Finds labels that are never jumped to and lines that can never be executed.
+    ///
+    /// Returns a list of 0-indexed line numbers and warnings about those lines, in line order.
+    fn find_unreachable(&self) -> Vec<(usize, String)> {
+        let mut warnings = Vec::new();
+
+        // an indirect jump could go to any label
+        let has_indirect_jump = self
+            .instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::JumpIndirect(_)));
+        if !has_indirect_jump {
+            let targeted_labels: HashSet<&str> = self
+                .instructions
+                .iter()
+                .filter_map(|instruction| instruction.jump_target())
+                .map(|target| target.label.as_str())
+                .collect();
+            for (label, line_number) in &self.labels {
+                if !targeted_labels.contains(label.as_str()) {
+                    warnings.push((*line_number, format!("label is never jumped to: {}", label)));
+                }
+            }
+        }
+
+        // anything after an instruction that always jumps can only be reached by jumping to a label after it
+        let mut unreachable_start = None;
+        for (line_number, instruction) in self.instructions.iter().enumerate() {
+            if let Instruction::Label(_) = instruction {
+                if let Some(start) = unreachable_start.take() {
+                    warnings.push(Program::unreachable_lines_warning(start, line_number));
+                }
+            } else if unreachable_start.is_none()
+                && line_number > 0
+                && self.instructions[line_number - 1].always_jumps()
+            {
+                unreachable_start = Some(line_number);
+            }
+        }
+        if let Some(start) = unreachable_start {
+            warnings.push(Program::unreachable_lines_warning(
+                start,
+                self.instructions.len(),
+            ));
+        }
+
+        warnings.sort_by_key(|(line_number, _)| *line_number);
+        warnings
+    }
+
+    /// Finds labels that are defined again after they've already been defined.
+    /// Only the last definition of a label is ever jumped to, so the earlier ones are most likely mistakes.
+    ///
+    /// Returns a list of the 0-indexed line numbers of every definition after the first, the line number of the first definition, and the name of the label, in line order.
+    fn find_duplicate_labels(&self) -> Vec<(usize, usize, &str)> {
+        let mut first_definitions: HashMap<&str, usize> = HashMap::new();
+        let mut duplicates = Vec::new();
+        for (line_number, instruction) in self.instructions.iter().enumerate() {
+            if let Instruction::Label(label) = instruction {
+                match first_definitions.get(label.as_str()) {
+                    Some(first) => duplicates.push((line_number, *first, label.as_str())),
+                    None => {
+                        first_definitions.insert(label, line_number);
+                    }
+                }
+            }
+        }
+        duplicates
+    }
+
+    /// Builds a warning about a range of lines that can never be executed.
+    ///
+    /// # Arguments
+    /// * `start`: The 0-indexed line number of the first unreachable line.
+    /// * `end`: The 0-indexed line number after the last unreachable line.
+    fn unreachable_lines_warning(start: usize, end: usize) -> (usize, String) {
+        if end - start == 1 {
+            (start, "line can never be executed".to_string())
+        } else {
+            // the warning is reported at the first line, and the range may not be contiguous in the source files if it spans an included file
+            (
+                start,
+                format!("{} lines starting here can never be executed", end - start),
+            )
+        }
+    }
+
+    /// Finds all the labels defined in the provided program.
+    ///
+    /// # Arguments
+    /// * `instructions`: The instructions of the program.
+    ///
+    /// Returns a map of label names to the lines they are defined on.
+    fn find_labels(instructions: &[Instruction]) -> HashMap<String, usize> {
+        let mut labels: HashMap<String, usize> = HashMap::new();
+        for (line_number, instruction) in instructions.iter().enumerate() {
+            if let Instruction::Label(label_name) = instruction {
+                labels.insert(label_name.to_string(), line_number);
+            }
+        }
+        labels
+    }
+
+    /// Gets the names of the labels defined in this program, in the order they're defined in.
+    pub fn label_names(&self) -> Vec<&str> {
+        let mut labels: Vec<(&String, &usize)> = self.labels.iter().collect();
+        labels.sort_unstable_by_key(|(_, line_number)| **line_number);
+        labels
+            .into_iter()
+            .map(|(label, _)| label.as_str())
+            .collect()
+    }
+
+    /// Gets the names of the constants defined in this program, sorted alphabetically.
+    pub fn constant_names(&self) -> Vec<&str> {
+        let mut constants: Vec<&str> = self.constants.keys().map(String::as_str).collect();
+        constants.sort_unstable();
+        constants
+    }
+
+    /// Gets the line numbers of the labels in this program, in the order they're defined in.
+    fn label_line_numbers(&self) -> &[usize] {
+        &self.label_line_numbers
+    }
+
+    /// Sets the line number of every jump target in the provided instructions to the line its label is defined on.
+    ///
+    /// # Arguments
+    /// * `instructions`: The instructions of the program.
+    /// * `labels`: Map of label names to the lines they are defined on.
+    /// * `errors`: Where to add an error for each jump target that refers to an unknown label.
+    fn resolve_jump_targets(
+        instructions: &mut [Instruction],
+        labels: &HashMap<String, usize>,
+        errors: &mut Vec<Error>,
+    ) {
+        for (line_number, instruction) in instructions.iter_mut().enumerate() {
+            if let Some(target) = instruction.jump_target_mut() {
+                match labels.get(&target.label) {
+                    Some(x) => target.line_number = *x,
+                    None => errors.push(Error::new(
+                        &format!("unknown label: {}", target.label),
+                        line_number,
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Parses a single line of source code into an `Instruction`.
+    ///
+    /// # Arguments
+    /// * `line`: The line to parse.
+    /// * `line_number`: The 0-indexed line number of the line, used in error messages.
+    /// * `options`: Options that affect how the line is parsed.
+    fn parse_line(
+        line: &str,
+        line_number: usize,
+        options: &ParseOptions,
+    ) -> Result<Instruction, Error> {
+        match match_operation(line) {
+            Some((op, operands)) => {
+                // the pattern only removes the operation name from the start of the line, so the operands start right after it
+                let operands_start = line.len() - operands.len();
+                (op.parse)(&operands, options)
+                    .map_err(|e| e.into_error(line_number, operands_start))
+            }
+            None => Err(Error::new("unexpected expression", line_number)),
+        }
+    }
+}
+
+/// A representation of the state of "memory" during the execution of a program.
+pub struct Context<'a> {
+    /// The program being executed.
+    program: Rc<Program>,
+    /// Where each line of the program was read from, or nothing if the lines weren't read from source code that can be referred back to.
+    origins: Vec<Origin>,
+    /// Map of register names to their current values.
+    registers: HashMap<String, i32>,
+    /// The names of the registers, in the order they're defined in.
+    register_names: Vec<String>,
+    /// The 0-indexed line number currently being executed.
+    current_line_number: usize,
+    /// The 0-indexed line numbers of the calls that haven't been returned from yet, most recent last.
+    call_stack: Vec<usize>,
+    /// What to do when the result of an arithmetic operation overflows.
+    overflow: OverflowBehavior,
+    /// How to round the result of a division that isn't exact.
+    division: DivisionBehavior,
+    /// Where input is read from.
+    input: Box<dyn BufRead + 'a>,
+    /// Where printed values are written to.
+    output: Box<dyn Write + 'a>,
+    /// The source of random numbers.
+    rng: StdRng,
+    /// The value to store when reading from stdin reaches the end of the input.
+    eof: i32,
+    /// Bytes that have been read from stdin but not stored in a register yet.
+    input_buffer: VecDeque<u8>,
+    /// Integers to store when reading from stdin before anything is actually read from it, in order.
+    args: VecDeque<i32>,
+    /// Whether to pause in the debugger before executing the next line.
+    stepping: bool,
+    /// Map of the 0-indexed line numbers of the labels to pause in the debugger after to the names of those labels.
+    breakpoints: HashMap<usize, String>,
+    /// The number of lines executed so far.
+    steps: u64,
+    /// The number of jumps taken so far, including calls and returns.
+    jumps: u64,
+    /// The number of bytes read from stdin so far.
+    bytes_read: u64,
+    /// The number of bytes printed so far.
+    bytes_printed: u64,
+    /// The maximum number of lines to execute, or `None` for no limit.
+    max_steps: Option<u64>,
+    /// How long the program can run for, or `None` for no limit.
+    timeout: Option<Duration>,
+    /// When the program has to finish by, or `None` for no limit.
+    deadline: Option<Instant>,
+    /// Whether to write the line number, source code, and resulting register values of each executed line to stderr.
+    trace: bool,
+    /// The number of times each line has been executed, indexed by 0-indexed line number.
+    execution_counts: Vec<u64>,
+    /// The most recently printed character, if any.
+    last_printed: Option<char>,
+    /// Where to write the value in every register after each line is executed, or `None` to not write them anywhere.
+    record: Option<BufWriter<File>>,
+}
+
+impl fmt::Debug for Context<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("program", &self.program)
+            .field("origins", &self.origins)
+            .field("registers", &self.registers)
+            .field("register_names", &self.register_names)
+            .field("current_line_number", &self.current_line_number)
+            .field("call_stack", &self.call_stack)
+            .field("overflow", &self.overflow)
+            .field("division", &self.division)
+            .field("eof", &self.eof)
+            .field("input_buffer", &self.input_buffer)
+            .field("args", &self.args)
+            .field("stepping", &self.stepping)
+            .field("breakpoints", &self.breakpoints)
+            .field("steps", &self.steps)
+            .field("jumps", &self.jumps)
+            .field("bytes_read", &self.bytes_read)
+            .field("bytes_printed", &self.bytes_printed)
+            .field("max_steps", &self.max_steps)
+            .field("timeout", &self.timeout)
+            .field("deadline", &self.deadline)
+            .field("trace", &self.trace)
+            .field("execution_counts", &self.execution_counts)
+            .field("last_printed", &self.last_printed)
+            .field("record", &self.record)
+            .finish()
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Creates a new `Context` for a program.
+    ///
+    /// # Arguments
+    /// * `program`: The program to execute.
+    /// * `settings`: Settings that control how the program is run.
+    /// * `input`: Where input should be read from.
+    /// * `output`: Where printed values should be written to.
+    fn new(
+        program: Rc<Program>,
+        settings: &Settings,
+        input: Box<dyn BufRead + 'a>,
+        output: Box<dyn Write + 'a>,
+    ) -> Context<'a> {
+        let rng = match settings.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let breakpoints =
+            Context::resolve_breakpoints(&program, &settings.breakpoints, settings.case_sensitive);
+        let execution_counts = vec![0; program.instructions.len()];
+        let register_names = all_register_names(settings);
+        Context {
+            program,
+            origins: Vec::new(),
+            registers: register_names
+                .iter()
+                .map(|name| (name.clone(), 0))
+                .collect(),
+            register_names,
+            current_line_number: 0,
+            call_stack: Vec::new(),
+            overflow: settings.overflow,
+            division: settings.division,
+            input,
+            output,
+            rng,
+            eof: settings.eof,
+            input_buffer: VecDeque::new(),
+            args: settings.args.iter().copied().collect(),
+            stepping: settings.debug,
+            breakpoints,
+            steps: 0,
+            jumps: 0,
+            bytes_read: 0,
+            bytes_printed: 0,
+            max_steps: settings.max_steps,
+            timeout: settings.timeout,
+            deadline: settings.timeout.map(|timeout| Instant::now() + timeout),
+            trace: settings.trace,
+            execution_counts,
+            last_printed: None,
+            record: None,
+        }
+    }
+
+    /// Replaces the program being executed, keeping the values in the registers and everything else about the current state.
+    ///
+    /// # Arguments
+    /// * `program`: The program to execute from now on.
+    fn set_program(&mut self, program: Rc<Program>) {
+        self.execution_counts.resize(program.instructions.len(), 0);
+        self.program = program;
+    }
+
+    /// Finds the lines the provided labels are defined on, so the debugger can pause after reaching them.
+    /// Labels that aren't defined in the program are reported and ignored.
+    ///
+    /// # Arguments
+    /// * `program`: The program to execute.
+    /// * `labels`: The labels to pause after.
+    /// * `case_sensitive`: Whether the labels are case-sensitive, so they shouldn't be lowercased.
+    ///
+    /// Returns a map of 0-indexed line numbers to the labels defined on them.
+    fn resolve_breakpoints(
+        program: &Program,
+        labels: &[String],
+        case_sensitive: bool,
+    ) -> HashMap<usize, String> {
+        let mut breakpoints = HashMap::new();
+        for label in labels {
+            let label = if case_sensitive {
+                label.trim().to_string()
+            } else {
+                label.trim().to_lowercase()
+            };
+            match program.labels.get(&label) {
+                Some(line_number) => {
+                    breakpoints.insert(*line_number, label);
+                }
+                None => eprintln!("ignoring breakpoint for unknown label: {}", label),
+            }
+        }
+        breakpoints
+    }
+
+    /// Writes any buffered output.
+    fn flush_output(&mut self) -> Result<(), Error> {
+        self.output.flush().map_err(|e| {
+            Error::new(
+                &format!("error writing to stdout: {}", e),
+                self.current_line_number,
+            )
+        })
+    }
+
+    /// Starts writing the value in every register to a CSV file after each line is executed.
+    ///
+    /// # Arguments
+    /// * `path`: The path of the file to write to. If it already exists, it's replaced.
+    fn start_recording(&mut self, path: &str) -> Result<(), Error> {
+        let error = |e: std::io::Error| Error::new(&format!("cannot write to {}: {}", path, e), 0);
+        let mut record = BufWriter::new(File::create(path).map_err(error)?);
+        writeln!(record, "step,register,value").map_err(error)?;
+        self.record = Some(record);
+        Ok(())
+    }
+
+    /// Writes the value in every register to the record file, if there is one, in the order the registers are defined in.
+    fn record_registers(&mut self) -> Result<(), Error> {
+        let record = match &mut self.record {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let line_number = self.current_line_number;
+        for name in &self.register_names {
+            writeln!(record, "{},{},{}", self.steps, name, self.registers[name]).map_err(|e| {
+                Error::new(
+                    &format!("error writing register record: {}", e),
+                    line_number,
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Writes any buffered register values to the record file, if there is one.
+    fn flush_record(&mut self) -> Result<(), Error> {
+        let line_number = self.current_line_number;
+        match &mut self.record {
+            Some(record) => record.flush().map_err(|e| {
+                Error::new(
+                    &format!("error writing register record: {}", e),
+                    line_number,
+                )
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Describes the current value of every register, in the order the registers are defined in.
+    fn describe_registers(&self) -> String {
+        self.register_names
+            .iter()
+            .map(|name| format!("{} = {}", name, self.registers[name]))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Describes where a line of the program was read from, like `file.business:3`, or gives its 1-indexed line number in the program if that isn't known.
+    ///
+    /// # Arguments
+    /// * `line_number`: The 0-indexed line number of the line in the program.
+    fn location(&self, line_number: usize) -> String {
+        match self.origins.get(line_number) {
+            Some(origin) => origin.to_string(),
+            None => (line_number + 1).to_string(),
+        }
+    }
+
+    /// Writes how many times each line has been executed to stderr, most executed first.
+    fn print_profile(&self) {
+        let mut line_numbers: Vec<usize> = (0..self.execution_counts.len()).collect();
+        line_numbers
+            .sort_by_key(|line_number| std::cmp::Reverse(self.execution_counts[*line_number]));
+        eprintln!("executions\tline\tsource");
+        for line_number in line_numbers {
+            eprintln!(
+                "{}\t{}\t{}",
+                self.execution_counts[line_number],
+                self.location(line_number),
+                self.program.source[line_number]
+            );
+        }
+    }
+
+    /// Writes the number of lines executed, jumps taken, bytes read, and bytes printed to stderr.
+    fn print_stats(&self) {
+        eprintln!("lines executed\t{}", self.steps);
+        eprintln!("jumps taken\t{}", self.jumps);
+        eprintln!("bytes read\t{}", self.bytes_read);
+        eprintln!("bytes printed\t{}", self.bytes_printed);
+    }
+
+    /// Executes the instruction at `program.instructions[current_line_number]` and sets `current_line_number` to the index of the next line to execute.
+    fn execute_current_line(&mut self) -> Result<(), Error> {
+        let program = Rc::clone(&self.program);
+        let instruction = match program.instructions.get(self.current_line_number) {
+            Some(x) => x,
+            None => return Err(Error::new("invalid line number", self.current_line_number)),
+        };
+        if let Some(max_steps) = self.max_steps {
+            if self.steps >= max_steps {
+                return Err(Error::new(
+                    &format!("exceeded maximum step count of {}", max_steps),
+                    self.current_line_number,
+                ));
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            // checking the time is slow compared to executing a line, so it's only done every so often
+            if self.steps.is_multiple_of(TIMEOUT_CHECK_INTERVAL) && Instant::now() >= deadline {
+                return Err(Error::new(
+                    &format!(
+                        "exceeded time limit of {} seconds",
+                        self.timeout.unwrap_or_default().as_secs_f64()
+                    ),
+                    self.current_line_number,
+                ));
+            }
+        }
+        self.steps += 1;
+        if self.stepping && !debugger::pause(self)? {
+            self.current_line_number = program.instructions.len();
+            return Ok(());
+        }
+        debug!(
+            "executing line {}: {}",
+            self.current_line_number, program.source[self.current_line_number]
+        );
+
+        trace!("registers before: {:?}", self.registers);
+        let line_number = self.current_line_number;
+        self.execution_counts[line_number] += 1;
+        operations::execute(instruction, self)?;
+        trace!("registers after: {:?}", self.registers);
+        if self.trace {
+            eprintln!(
+                "{}\t{}\t{}",
+                self.location(line_number),
+                program.source[line_number],
+                self.describe_registers()
+            );
+        }
+        self.record_registers()?;
+        // this is either the line that was just executed or the label that was jumped to
+        let reached_label = self.breakpoints.get(&self.current_line_number).cloned();
+        self.current_line_number += 1;
+        if let Some(label) = reached_label {
+            self.flush_output()?;
+            eprintln!("reached breakpoint after label: {}", label);
+            self.stepping = true;
+            // a label at the end of the program has no line to pause before, so pause now instead
+            if self.current_line_number >= program.instructions.len() {
+                debugger::pause(self)?;
+            }
+        }
+        Ok(())
+    }
+}
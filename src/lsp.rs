@@ -0,0 +1,286 @@
+use crate::include::UNNAMED_SOURCE;
+use crate::{check_program, split_lines, Error, Settings};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// The JSON-RPC error code for a request with a method the server doesn't support.
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// Runs a language server that reads Language Server Protocol messages from `input` and writes responses to `output`, until it's told to exit.
+/// Whenever a document is opened or changed, it's parsed, and any error is published as a diagnostic,
+/// along with errors about labels that are defined more than once, and warnings about labels that are never jumped to and lines that can never be executed.
+///
+/// # Arguments
+/// * `settings`: Settings that control how documents are parsed.
+/// * `input`: Where messages from the client should be read from.
+/// * `output`: Where messages to the client should be written to.
+///
+/// Returns `Err(Error)` if reading from `input` or writing to `output` fails, or if a message isn't framed correctly.
+pub fn run<'a>(
+    settings: Settings,
+    mut input: Box<dyn BufRead + 'a>,
+    mut output: Box<dyn Write + 'a>,
+) -> Result<(), Error> {
+    while let Some(message) = read_message(&mut input)? {
+        let id = &message["id"];
+        let params = &message["params"];
+        match message["method"].as_str().unwrap_or("") {
+            "initialize" => {
+                let result = json!({
+                    // 1 means the whole document is sent every time it changes
+                    "capabilities": { "textDocumentSync": 1 },
+                    "serverInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                });
+                respond(&mut output, id, result)?;
+            }
+            "shutdown" => respond(&mut output, id, Value::Null)?,
+            "exit" => return Ok(()),
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let uri = document["uri"].as_str().unwrap_or("");
+                let text = document["text"].as_str().unwrap_or("");
+                publish_diagnostics(&mut output, uri, check(uri, text, &settings))?;
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                // every change contains the whole document, so only the last one matters
+                let changes = params["contentChanges"].as_array();
+                if let Some(change) = changes.and_then(|changes| changes.last()) {
+                    let text = change["text"].as_str().unwrap_or("");
+                    publish_diagnostics(&mut output, uri, check(uri, text, &settings))?;
+                }
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                publish_diagnostics(&mut output, uri, Vec::new())?;
+            }
+            method => {
+                // notifications have no ID and don't need a response, even if they aren't supported
+                if !id.is_null() {
+                    let error = json!({
+                        "code": METHOD_NOT_FOUND,
+                        "message": format!("unsupported method: {}", method),
+                    });
+                    send(
+                        &mut output,
+                        json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+                    )?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses a document and describes any problems with it as diagnostics.
+///
+/// # Arguments
+/// * `uri`: The URI of the document. If it's a `file:` URI, files included by the document are found relative to it.
+/// * `text`: The text of the document.
+/// * `settings`: Settings that control how the document is parsed.
+fn check(uri: &str, text: &str, settings: &Settings) -> Vec<Value> {
+    let file = uri.strip_prefix("file://").map(decode_uri);
+    let settings = Settings {
+        file: file.clone(),
+        ..settings.clone()
+    };
+    let lines = split_lines(text);
+
+    let name = file.as_deref().unwrap_or(UNNAMED_SOURCE);
+    let (program, origins) = match check_program(text, name, &settings) {
+        Ok(x) => x,
+        Err(mut errors) => {
+            // a line can have more than one problem, but only the first one found is shown
+            errors.sort_by_key(|e| e.line_number);
+            errors.dedup_by_key(|e| e.line_number);
+            return errors
+                .into_iter()
+                .map(|e| match &e.origin {
+                    Some(origin) if Some(&origin.file) == file.as_ref() || file.is_none() => {
+                        diagnostic(&lines, origin.line_number, e.column, 1, &e.message)
+                    }
+                    // errors in included files are shown at the start of the document
+                    Some(origin) => {
+                        let message = format!("{}: {}", origin, e.message);
+                        diagnostic(&lines, 0, None, 1, &message)
+                    }
+                    None => diagnostic(&lines, e.line_number, e.column, 1, &e.message),
+                })
+                .collect();
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    for (line_number, first, label) in program.find_duplicate_labels() {
+        let first = &origins[first];
+        let message = format!("label is already defined at {}: {}", first, label);
+        let origin = &origins[line_number];
+        if Some(&origin.file) == file.as_ref() || file.is_none() {
+            diagnostics.push(diagnostic(&lines, origin.line_number, None, 1, &message));
+        } else {
+            // like other errors in included files, these are shown at the start of the document
+            let message = format!("{}: {}", origin, message);
+            diagnostics.push(diagnostic(&lines, 0, None, 1, &message));
+        }
+    }
+
+    diagnostics.extend(
+        program
+            .find_unreachable()
+            .into_iter()
+            .map(|(line_number, warning)| (&origins[line_number], warning))
+            .filter(|(origin, _)| Some(&origin.file) == file.as_ref() || file.is_none())
+            .map(|(origin, warning)| diagnostic(&lines, origin.line_number, None, 2, &warning)),
+    );
+    diagnostics
+}
+
+/// Builds a diagnostic that covers the rest of a line of a document.
+///
+/// # Arguments
+/// * `lines`: The lines of the document.
+/// * `line_number`: The 0-indexed line number the diagnostic is on.
+/// * `column`: The 0-indexed column in the trimmed line the diagnostic starts at, or `None` to cover the whole line.
+/// * `severity`: How serious the diagnostic is: 1 for an error, or 2 for a warning.
+/// * `message`: A message describing the problem.
+fn diagnostic(
+    lines: &[&str],
+    line_number: usize,
+    column: Option<usize>,
+    severity: u8,
+    message: &str,
+) -> Value {
+    let line = lines.get(line_number).copied().unwrap_or("");
+    let indent = line.len() - line.trim_start().len();
+    let start = indent + column.unwrap_or(0);
+    let end = line.trim_end().len().max(start);
+    json!({
+        "range": {
+            "start": { "line": line_number, "character": utf16_length(line, start) },
+            "end": { "line": line_number, "character": utf16_length(line, end) },
+        },
+        "severity": severity,
+        "source": env!("CARGO_PKG_NAME"),
+        "message": message,
+    })
+}
+
+/// Counts the UTF-16 code units in the first bytes of a line, which is how positions in a line are measured by the Language Server Protocol.
+///
+/// # Arguments
+/// * `line`: The line to measure.
+/// * `bytes`: The number of bytes at the start of the line to measure. Anything past the end of the line is counted as one code unit per byte.
+fn utf16_length(line: &str, bytes: usize) -> usize {
+    let measured: usize = line
+        .char_indices()
+        .take_while(|(i, _)| *i < bytes)
+        .map(|(_, c)| c.len_utf16())
+        .sum();
+    measured + bytes.saturating_sub(line.len())
+}
+
+/// Decodes the percent-encoded bytes in the path of a URI.
+fn decode_uri(path: &str) -> String {
+    let mut decoded = Vec::new();
+    let mut bytes = path.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex: Vec<u8> = bytes.clone().take(2).collect();
+            if hex.len() == 2 && hex.iter().all(u8::is_ascii_hexdigit) {
+                let x = u8::from_str_radix(std::str::from_utf8(&hex).unwrap(), 16).unwrap();
+                decoded.push(x);
+                bytes.nth(1);
+                continue;
+            }
+        }
+        decoded.push(b);
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Reads a message from the client.
+///
+/// Returns the message, or `None` if the input ended before another message started.
+fn read_message(input: &mut dyn BufRead) -> Result<Option<Value>, Error> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        match input.read_line(&mut header) {
+            Ok(0) if content_length.is_none() => return Ok(None),
+            Ok(0) => return Err(Error::new("incomplete message header", 0)),
+            Ok(_) => (),
+            Err(e) => return Err(Error::new(&format!("error reading from stdin: {}", e), 0)),
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = match content_length {
+        Some(x) => x,
+        None => return Err(Error::new("message has no content length", 0)),
+    };
+    let mut content = vec![0; content_length];
+    input
+        .read_exact(&mut content)
+        .map_err(|e| Error::new(&format!("error reading from stdin: {}", e), 0))?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| Error::new(&format!("invalid message: {}", e), 0))
+}
+
+/// Sends a successful response to a request.
+///
+/// # Arguments
+/// * `output`: Where to write the response.
+/// * `id`: The ID of the request being responded to.
+/// * `result`: The result of the request.
+fn respond(output: &mut dyn Write, id: &Value, result: Value) -> Result<(), Error> {
+    send(
+        output,
+        json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    )
+}
+
+/// Sends the diagnostics for a document, replacing any that were sent for it before.
+///
+/// # Arguments
+/// * `output`: Where to write the notification.
+/// * `uri`: The URI of the document.
+/// * `diagnostics`: The diagnostics for the document.
+fn publish_diagnostics(
+    output: &mut dyn Write,
+    uri: &str,
+    diagnostics: Vec<Value>,
+) -> Result<(), Error> {
+    send(
+        output,
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }),
+    )
+}
+
+/// Writes a message to the client.
+fn send(output: &mut dyn Write, message: Value) -> Result<(), Error> {
+    let content = message.to_string();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )
+    .and_then(|_| output.flush())
+    .map_err(|e| Error::new(&format!("error writing to stdout: {}", e), 0))
+}
@@ -1,124 +1,127 @@
-mod operations;
-
 use clap::Clap;
-use regex::Regex;
-use std::collections::HashMap;
-use std::error::Error;
-use std::fmt;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-
-#[macro_use]
-extern crate log;
-
-#[macro_use]
-extern crate lazy_static;
-
-const REGISTER_NAMES: [&str; 8] = [
-    "customer experience",
-    "revenue streams",
-    "core competencies",
-    "best practices",
-    "stakeholder engagement",
-    "key performance indicators",
-    "return on investment",
-    "assets",
-];
-
-/// Strings that can be placed between operands.
-const OPERAND_CONNECTORS: [&str; 3] = [" and ", " with ", " to "];
-
-/// Strings that can be placed between literals.
-const LITERAL_CONNECTORS: [&str; 3] = [", and ", " and ", ", "];
-
-lazy_static! {
-    /// Map of literals to the values they represent.
-    static ref LITERALS: HashMap<String, u8> = {
-        let mut map = HashMap::new();
-        map.insert("hr".to_string(), 0);
-        map.insert("engineering".to_string(), 1);
-        map.insert("legal".to_string(), 2);
-        map.insert("pr".to_string(), 3);
-        map.insert("finance".to_string(), 4);
-        map.insert("marketing".to_string(), 5);
-        map.insert("r&d".to_string(), 6);
-        map.insert("sales".to_string(), 7);
-        map.insert("manufacturing".to_string(), 8);
-        map.insert("executive management".to_string(), 9);
-        map
-    };
-    /// The pattern for lines that define a label.
-    static ref LABEL_PATTERN: Regex = Regex::new("^(moving|going) forward, ").unwrap();
-    static ref OPERATIONS: [Operation; 15] = [
-        Operation {
-            pattern: LABEL_PATTERN.clone(),
-            func: operations::no_op
-        },
-        Operation {
-            pattern: Regex::new("^(innovate|value-add) ").unwrap(),
-            func: operations::increment
-        },
-        Operation {
-            pattern: Regex::new("^(streamline|optimize) ").unwrap(),
-            func: operations::decrement
-        },
-        Operation {
-            pattern: Regex::new("^(revamp|overhaul) ").unwrap(),
-            func: operations::negate
-        },
-        Operation {
-            pattern: Regex::new("^(amplify|incentivize) ").unwrap(),
-            func: operations::double
-        },
-        Operation {
-            pattern: Regex::new("^backburner ").unwrap(),
-            func: operations::halve
-        },
-        Operation {
-            pattern: Regex::new("^paradigm shift ").unwrap(),
-            func: operations::randomize
-        },
-        Operation {
-            pattern: Regex::new("^align ").unwrap(),
-            func: operations::assign
-        },
-        Operation {
-            pattern: Regex::new("^(synergize|integrate) ").unwrap(),
-            func: operations::add
-        },
-        Operation {
-            pattern: Regex::new("^differentiate ").unwrap(),
-            func: operations::subtract
-        },
-        Operation {
-            pattern: Regex::new("^crowdsource ").unwrap(),
-            func: operations::read
-        },
-        Operation {
-            pattern: Regex::new("^(deliver|produce) ").unwrap(),
-            func: operations::print
-        },
-        Operation {
-            pattern: Regex::new("^(circle back to|revisit) ").unwrap(),
-            func: operations::jump
-        },
-        Operation {
-            pattern: Regex::new("^pivot ").unwrap(),
-            func: operations::jump_if_zero
-        },
-        Operation {
-            pattern: Regex::new("^restructure ").unwrap(),
-            func: operations::jump_if_neg
-        },
-    ];
-}
+use std::io::Read;
+use std::time::Duration;
+use strategic_communication::{DivisionBehavior, OverflowBehavior, Settings};
 
 /// Interpreter for the programming language Strategic Communication.
 /// More information can be found at https://github.com/rotoclone/strategic-communication/blob/master/README.md
 #[derive(Clap)]
 #[clap(version = env!("CARGO_PKG_VERSION"))]
 struct Opts {
-    /// The path to the file containing source code to execute
-    file: String,
+    /// The path to the file containing source code to execute, or - to read it from stdin
+    #[clap(required_unless_present_any = &["repl", "lsp", "batch", "help-language"])]
+    file: Option<String>,
+    /// Read lines of source code from stdin and execute each one as it's entered
+    #[clap(long)]
+    repl: bool,
+    /// Run every program in this directory that has a .expected file next to it, and report whether its output matched
+    #[clap(long, value_name = "dir", conflicts_with_all = &["repl", "lsp", "debug", "record"])]
+    batch: Option<String>,
+    /// Print every operation, register, and literal the language has, then exit
+    #[clap(long)]
+    help_language: bool,
+    /// Run a language server that reports errors in programs to an editor, communicating over stdin and stdout
+    #[clap(long)]
+    lsp: bool,
+    /// What to do when the result of an arithmetic operation doesn't fit in a register
+    #[clap(long, arg_enum, default_value = "wrap")]
+    overflow: OverflowBehavior,
+    /// How to round the result of a division that isn't exact: toward zero, or toward negative infinity, which also gives remainders the sign of the divisor
+    #[clap(long, arg_enum, default_value = "trunc")]
+    division: DivisionBehavior,
+    /// Write output after every line instead of only when the program reads input or finishes
+    #[clap(long)]
+    line_buffered: bool,
+    /// Seed for the random number generator, to make programs that use randomness reproducible
+    #[clap(long)]
+    seed: Option<u64>,
+    /// The value to store in a register when reading from stdin reaches the end of the input
+    #[clap(long, default_value = "-1", allow_hyphen_values = true)]
+    eof: i32,
+    /// An integer to store when reading input, before anything is read from stdin (can be provided more than once)
+    #[clap(
+        long = "arg",
+        value_name = "integer",
+        number_of_values = 1,
+        allow_hyphen_values = true
+    )]
+    args: Vec<i32>,
+    /// Add the registers named in this file, one per line, to the standard ones
+    #[clap(long = "registers", value_name = "path")]
+    register_file: Option<String>,
+    /// Start a register with this value instead of 0 (can be provided more than once)
+    #[clap(
+        long = "set",
+        value_name = "register=value",
+        number_of_values = 1,
+        allow_hyphen_values = true,
+        parse(try_from_str = parse_register_value)
+    )]
+    initial_registers: Vec<(String, i32)>,
+    /// Read input for the program from this string instead of stdin
+    #[clap(long, value_name = "string", conflicts_with_all = &["input-file", "repl", "lsp", "debug"])]
+    input: Option<String>,
+    /// Read input for the program from this file instead of stdin
+    #[clap(long, value_name = "path", conflicts_with_all = &["repl", "lsp", "debug"])]
+    input_file: Option<String>,
+    /// Pause before each line and wait for debugger commands on stdin
+    #[clap(long)]
+    debug: bool,
+    /// Pause and wait for debugger commands after this label (can be provided more than once)
+    #[clap(
+        long = "break",
+        value_name = "label",
+        number_of_values = 1,
+        conflicts_with = "repl"
+    )]
+    breakpoints: Vec<String>,
+    /// Stop the program with an error after executing this many lines
+    #[clap(long)]
+    max_steps: Option<u64>,
+    /// Stop the program with an error after it has run for this many seconds
+    #[clap(long, value_name = "seconds", parse(try_from_str = parse_timeout))]
+    timeout: Option<Duration>,
+    /// Write each executed line and the resulting register values to stderr
+    #[clap(long)]
+    trace: bool,
+    /// Write how many times each line was executed to stderr when the program finishes
+    #[clap(long)]
+    profile: bool,
+    /// Write the number of lines executed, jumps taken, bytes read, and bytes printed to stderr when the program finishes
+    #[clap(long)]
+    stats: bool,
+    /// Write the value in every register after each executed line to this file as CSV
+    #[clap(long, value_name = "path")]
+    record: Option<String>,
+    /// Warn about labels that are never jumped to and lines that can never be executed
+    #[clap(long)]
+    warn_unreachable: bool,
+    /// Write the final value in each register as JSON to stdout, or to a file given as --dump-registers=<path>
+    #[clap(long, value_name = "path", min_values = 0, require_equals = true)]
+    dump_registers: Option<Option<String>>,
+    /// Write the program as C source code to this file instead of running it
+    #[clap(long, value_name = "path")]
+    emit_c: Option<String>,
+    /// Write the parsed program as JSON to this file instead of running it
+    #[clap(long, value_name = "path")]
+    emit_ast: Option<String>,
+    /// Write the control flow graph of the program in Graphviz DOT format to this file instead of running it
+    #[clap(long, value_name = "path")]
+    emit_cfg: Option<String>,
+    /// Write the program to stdout with consistent connectors and whitespace instead of running it
+    #[clap(long)]
+    format: bool,
+    /// Write literals in base 16, and allow the literals for 10 through 15
+    #[clap(long)]
+    hex: bool,
+    /// Only allow labels as operands of jumps, so a misspelled register is always reported as one
+    #[clap(long)]
+    strict: bool,
+    /// Keep the case of the source code, so labels that differ only by case are different
+    #[clap(long)]
+    case_sensitive: bool,
 }
 
 fn main() {
@@ -126,144 +129,173 @@ fn main() {
 
     let opts = Opts::parse();
 
-    let source = fs::read_to_string(opts.file).expect("cannot open file");
-    let source: Vec<String> = source
-        .split('\n')
-        .map(|line| line.trim().to_lowercase())
-        .filter(|line| !line.is_empty())
-        .collect();
-
-    if let Err(e) = run(source) {
-        eprintln!("runtime error: {}", e);
-    }
-}
-
-/// Runs a program.
-///
-/// # Arguments
-/// * `source`: The source code of the program to run, split by line.
-///
-/// Returns `Err(RuntimeError)` if any errors occurred during the execution of the program.
-fn run(source: Vec<String>) -> Result<(), RuntimeError> {
-    let mut context = Context::new(source);
-    debug!("created context: {:?}", context);
-    while context.current_line_number < context.source.len() {
-        context.execute_current_line()?;
-    }
-    Ok(())
-}
+    let input = match (opts.input, opts.input_file) {
+        (Some(input), _) => Some(input.into_bytes()),
+        (None, Some(path)) => Some(fs::read(path).expect("cannot open input file")),
+        (None, None) => None,
+    };
 
-/// An error during the execution of a program.
-#[derive(Debug)]
-pub struct RuntimeError {
-    /// The 0-indexed line number the error occurred on.
-    line_number: usize,
-    /// A message describing the error.
-    message: String,
-}
+    let extra_registers = match &opts.register_file {
+        Some(path) => fs::read_to_string(path)
+            .expect("cannot open register file")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+        None => Vec::new(),
+    };
 
-impl Error for RuntimeError {}
+    let settings = Settings {
+        overflow: opts.overflow,
+        division: opts.division,
+        line_buffered: opts.line_buffered,
+        seed: opts.seed,
+        eof: opts.eof,
+        args: opts.args,
+        extra_registers,
+        initial_registers: opts.initial_registers,
+        input,
+        debug: opts.debug,
+        breakpoints: opts.breakpoints,
+        max_steps: opts.max_steps,
+        timeout: opts.timeout,
+        trace: opts.trace,
+        profile: opts.profile,
+        stats: opts.stats,
+        record: opts.record,
+        warn_unreachable: opts.warn_unreachable,
+        hex: opts.hex,
+        file: opts.file.clone().filter(|file| file != "-"),
+        case_sensitive: opts.case_sensitive,
+        strict: opts.strict,
+    };
 
-impl fmt::Display for RuntimeError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "error on line {}: {}",
-            self.line_number + 1,
-            self.message
-        )
+    if opts.help_language {
+        print!("{}", strategic_communication::language_reference(&settings));
+        return;
     }
-}
 
-impl RuntimeError {
-    /// Creates a new `RuntimeError` with the provided message.
-    fn new(message: &str, context: &Context) -> RuntimeError {
-        RuntimeError {
-            line_number: context.current_line_number,
-            message: message.to_string(),
+    if let Some(dir) = &opts.batch {
+        match strategic_communication::run_batch(dir, settings) {
+            Ok(true) => (),
+            Ok(false) => std::process::exit(1),
+            Err(e) => exit_with_error(e),
         }
+        return;
     }
-}
-
-/// Return type for operation execution functions.
-type OpResult = Result<(), RuntimeError>;
 
-/// An operation corresponding to a line of source code.
-struct Operation {
-    /// The regular expression to use to determine if a given line should cause this operation to be executed.
-    pattern: Regex,
-    /// The function that executes this operation.
-    func: fn(&str, &mut Context) -> OpResult,
-}
+    if opts.lsp {
+        if let Err(e) = strategic_communication::run_language_server(settings) {
+            exit_with_error(e);
+        }
+        return;
+    }
 
-/// A representation of the state of "memory" during the execution of a program.
-#[derive(Debug)]
-pub struct Context {
-    /// The source code of the program, split by line.
-    source: Vec<String>,
-    /// Map of register names to their current values.
-    registers: HashMap<String, i32>,
-    /// Map of label names to the lines they are defined on.
-    labels: HashMap<String, usize>,
-    /// The 0-indexed line number currently being executed.
-    current_line_number: usize,
-}
+    let file = match opts.file {
+        Some(x) if !opts.repl => x,
+        _ => {
+            if let Err(e) = strategic_communication::run_repl(settings) {
+                exit_with_error(e);
+            }
+            return;
+        }
+    };
+    let source = if file == "-" {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .expect("cannot read from stdin");
+        source
+    } else {
+        fs::read_to_string(&file).expect("cannot open file")
+    };
 
-impl Context {
-    /// Creates a new `Context` for a program.
-    ///
-    /// # Arguments
-    /// * `source`: The source code of the program, split by line.
-    fn new(source: Vec<String>) -> Context {
-        let labels = Context::find_labels(&source);
-        Context {
-            source,
-            registers: REGISTER_NAMES
-                .iter()
-                .map(|name| (name.to_string(), 0))
-                .collect(),
-            labels,
-            current_line_number: 0,
+    if opts.format {
+        match strategic_communication::format_source(&source, settings) {
+            Ok(formatted) => print!("{}", formatted),
+            Err(e) => exit_with_error(e),
         }
+        return;
     }
 
-    /// Finds all the labels defined in the provided program.
-    ///
-    /// # Arguments
-    /// * `source`: The source code of the program, split by line.
-    ///
-    /// Returns a map of label names to the lines they are defined on.
-    fn find_labels(source: &[String]) -> HashMap<String, usize> {
-        let mut labels: HashMap<String, usize> = HashMap::new();
-        for (line_number, line) in source.iter().enumerate() {
-            if LABEL_PATTERN.is_match(line) {
-                let label_name = LABEL_PATTERN.replace(line, "").to_string();
-                labels.insert(label_name, line_number);
+    if opts.emit_c.is_some() || opts.emit_ast.is_some() || opts.emit_cfg.is_some() {
+        if let Some(path) = opts.emit_ast {
+            match strategic_communication::emit_ast(&source, &file, settings.clone()) {
+                Ok(json) => fs::write(path, json + "\n").expect("cannot write parsed program"),
+                Err(e) => exit_with_error(e),
+            }
+        }
+        if let Some(path) = opts.emit_cfg {
+            match strategic_communication::emit_cfg(&source, settings.clone()) {
+                Ok(dot) => fs::write(path, dot).expect("cannot write control flow graph"),
+                Err(e) => exit_with_error(e),
+            }
+        }
+        if let Some(path) = opts.emit_c {
+            match strategic_communication::emit_c(&source, settings) {
+                Ok(c) => fs::write(path, c).expect("cannot write C source"),
+                Err(e) => exit_with_error(e),
             }
         }
-        labels
+        return;
     }
 
-    /// Executes the line at `source[current_line_number]` and sets `current_line_number` to the index of the next line to execute.
-    fn execute_current_line(&mut self) -> Result<(), RuntimeError> {
-        if self.current_line_number >= self.source.len() {
-            return Err(RuntimeError::new("invalid line number", self));
+    let name = if file == "-" { "<source>" } else { &file };
+    match strategic_communication::run_source(name, &source, settings) {
+        Ok(registers) => {
+            if let Some(path) = opts.dump_registers {
+                dump_registers(&registers, path.as_deref());
+            }
         }
+        Err(e) => exit_with_error(e),
+    }
+}
 
-        let line = &self.source[self.current_line_number];
-        debug!("executing line {}: {}", self.current_line_number, line);
+/// Writes an error to stderr and exits with a failure status.
+fn exit_with_error(error: impl std::fmt::Display) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
 
-        for op in OPERATIONS.iter() {
-            if op.pattern.is_match(line) {
-                let operands = op.pattern.replace(&line, "").to_string();
-                trace!("registers before: {:?}", self.registers);
-                (op.func)(&operands, self)?;
-                trace!("registers after: {:?}", self.registers);
-                self.current_line_number += 1;
-                return Ok(());
-            }
-        }
+/// Parses a register name and a value to start it with, separated by `=`.
+fn parse_register_value(s: &str) -> Result<(String, i32), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <register>=<value>, got {}", s))?;
+    let value = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", name.trim(), value.trim()))?;
+    Ok((name.to_string(), value))
+}
+
+/// Parses a number of seconds, which can include a fraction, into a time limit.
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    let seconds: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number of seconds: {}", s))?;
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(format!(
+            "timeout must be a positive number of seconds, got {}",
+            s
+        ));
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("timeout is too long: {}", s))
+}
 
-        Err(RuntimeError::new("unexpected expression", self))
+/// Writes register values as a JSON object.
+///
+/// # Arguments
+/// * `registers`: Map of register names to their values.
+/// * `path`: The path of the file to write to, or `None` to write to stdout.
+fn dump_registers(registers: &HashMap<String, i32>, path: Option<&str>) {
+    // sort the registers so the output is the same every time
+    let registers: BTreeMap<&String, &i32> = registers.iter().collect();
+    let json = serde_json::to_string_pretty(&registers).expect("cannot serialize registers");
+    match path {
+        Some(path) => fs::write(path, json + "\n").expect("cannot write register dump"),
+        None => println!("{}", json),
     }
 }
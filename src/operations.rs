@@ -1,288 +1,1045 @@
 use crate::{
-    Context, OpResult, RuntimeError, LITERALS, LITERAL_CONNECTORS, OPERAND_CONNECTORS,
-    REGISTER_NAMES,
+    Context, DivisionBehavior, Error, OpResult, OverflowBehavior, ParseOptions, ParseResult,
+    CONSTANT_SEPARATOR, LITERAL_CONNECTORS, NEGATIVE_LITERAL_PREFIX, OPERAND_CONNECTORS,
 };
+use rand::distributions::Uniform;
 use rand::Rng;
 use regex::Regex;
-use std::collections::hash_map::Entry::Occupied;
-use std::io::{Read, Write};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::io::{BufRead, Read, Write};
+
+lazy_static! {
+    /// The pattern for an optional literal connector.
+    static ref LITERAL_CONNECTOR_PATTERN: Regex =
+        Regex::new(&format!("^({})?", LITERAL_CONNECTORS.join("|"))).unwrap();
+    /// The pattern for an optional operand connector.
+    static ref OPERAND_CONNECTOR_PATTERN: Regex =
+        Regex::new(&format!("^({})?", OPERAND_CONNECTORS.join("|"))).unwrap();
+}
 
-/// Does nothing.
-pub fn no_op(operands: &str, _context: &mut Context) -> OpResult {
-    debug!("no op with operands: {}", operands);
-    Ok(())
+/// Defines a label.
+pub fn label(operands: &str, options: &ParseOptions) -> ParseResult {
+    if operands.trim().is_empty() {
+        return Err("label name cannot be empty".into());
+    }
+
+    // a name that starts with a register or literal would be parsed as that instead when it's jumped to
+    match parse_operands(operands, options)?.as_slice() {
+        [(Operand::Label(label), _)] if label == operands => {}
+        _ => {
+            return Err(ParseError::at(
+                &format!("invalid label name: {}", operands),
+                0,
+            ))
+        }
+    }
+
+    Ok(Instruction::Label(operands.to_string()))
 }
 
-/// Increments a register's value by 1.
-pub fn increment(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("increment with operands: {}", operands);
+/// Defines a named constant.
+/// The constants in a program are parsed with `parse_constant` before any of its lines are, so there's nothing left to parse here.
+pub fn define_constant(_operands: &str, _options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::DefineConstant)
+}
+
+/// Parses the definition of a named constant.
+///
+/// # Arguments
+/// * `operands`: The operands to parse, consisting of the name of the constant, followed by `be`, followed by a literal.
+/// * `options`: Options that affect how the operands are parsed.
+///
+/// Returns the name of the constant and its value.
+pub fn parse_constant(operands: &str, options: &ParseOptions) -> Result<(String, i32), ParseError> {
+    let separator_index = match operands.find(CONSTANT_SEPARATOR) {
+        Some(x) => x,
+        None => {
+            return Err("constant definition must be in the form: let <name> be <value>".into())
+        }
+    };
+    let name = &operands[..separator_index];
+    let value_start = separator_index + CONSTANT_SEPARATOR.len();
+
+    // a name that starts with a register or literal would be parsed as that instead
+    match parse_operands(name, options)?.as_slice() {
+        [(Operand::Label(label), _)]
+            if label == name
+                && !OPERAND_CONNECTORS
+                    .iter()
+                    .any(|connector| name.contains(connector)) => {}
+        _ => {
+            return Err(ParseError::at(
+                &format!("invalid constant name: {}", name),
+                0,
+            ))
+        }
+    }
+
+    match parse_operands(&operands[value_start..], options)?.as_slice() {
+        [(Operand::Literal(value), _)] => Ok((name.to_string(), *value)),
+        _ => Err(ParseError::at(
+            "value of constant must be a constant expression",
+            value_start,
+        )),
+    }
+}
 
-    Ok(modify_register(
+/// Increments a register's value by 1.
+pub fn increment(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Increment(parse_register(
         operands,
-        Transformation::Add(1),
-        &mut context,
-    )?)
+        options,
+        "increment",
+    )?))
 }
 
 /// Decrements a register's value by 1.
-pub fn decrement(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("decrement with operands: {}", operands);
-
-    Ok(modify_register(
+pub fn decrement(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Decrement(parse_register(
         operands,
-        Transformation::Add(-1),
-        &mut context,
-    )?)
+        options,
+        "decrement",
+    )?))
 }
 
 /// Multiplies a register's value by -1.
-pub fn negate(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("negate with operands: {}", operands);
+pub fn negate(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Negate(parse_register(
+        operands, options, "negate",
+    )?))
+}
 
-    Ok(modify_register(
+/// Flips all the bits of a register's value.
+pub fn complement(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Complement(parse_register(
         operands,
-        Transformation::Multiply(-1),
-        &mut context,
-    )?)
+        options,
+        "complement",
+    )?))
 }
 
 /// Multiplies a register's value by 2.
-pub fn double(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("double with operands: {}", operands);
-
-    Ok(modify_register(
-        operands,
-        Transformation::Multiply(2),
-        &mut context,
-    )?)
+pub fn double(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Double(parse_register(
+        operands, options, "double",
+    )?))
 }
 
 /// Divides a register's value by 2.
-pub fn halve(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("halve with operands: {}", operands);
+pub fn halve(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Halve(parse_register(
+        operands, options, "halve",
+    )?))
+}
+
+/// Sets a register's value to a random number between 0 and an optional literal upper bound (inclusive), which defaults to 9.
+pub fn randomize(operands: &str, options: &ParseOptions) -> ParseResult {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 1)?;
+    let mut operands = operands.into_iter();
+    let register = match operands.next() {
+        Some((Operand::Register(name), _)) => name,
+        Some((_, offset)) => {
+            return Err(ParseError::at(
+                "first operand for randomize must be a register",
+                offset,
+            ))
+        }
+        None => return Err("wrong number of operands for randomize".into()),
+    };
+    let upper_bound = match operands.next() {
+        Some((Operand::Literal(bound), _)) if bound > 0 => bound,
+        Some((Operand::Literal(_), offset)) => {
+            return Err(ParseError::at(
+                "upper bound for randomize must be positive",
+                offset,
+            ))
+        }
+        Some((_, offset)) => {
+            return Err(ParseError::at(
+                "second operand for randomize must be a literal",
+                offset,
+            ))
+        }
+        None => 9,
+    };
+    if let Some((_, offset)) = operands.next() {
+        return Err(ParseError::at(
+            "wrong number of operands for randomize",
+            offset,
+        ));
+    }
+
+    Ok(Instruction::Randomize(register, upper_bound))
+}
+
+/// Sets a register's value to 0.
+pub fn clear(operands: &str, options: &ParseOptions) -> ParseResult {
+    let register = parse_register(operands, options, "clear")?;
+
+    Ok(Instruction::Assign(register, Operand::Literal(0)))
+}
+
+/// Sets a register's value to the value in another register or a literal value.
+pub fn assign(operands: &str, options: &ParseOptions) -> ParseResult {
+    let operands = parse_operands(operands, options)?;
+    // a label can only be assigned to a register, so anything else that's parsed as a label is most likely a misspelled register
+    let label_allowed =
+        !options.strict && matches!(operands.first(), Some((Operand::Register(_), _)));
+    reject_labels(&operands, if label_allowed { 1 } else { 2 })?;
+    // should be either a register followed by a register, literal, or label, or a literal followed by a register
+    if operands.len() != 2 {
+        return Err("wrong number of operands for assignment".into());
+    }
+
+    let mut operands = operands.into_iter();
+    match (operands.next().unwrap(), operands.next().unwrap()) {
+        ((Operand::Register(to_register), _), (value @ Operand::Register(_), _))
+        | ((Operand::Register(to_register), _), (value @ Operand::Literal(_), _))
+        | ((value @ Operand::Literal(_), _), (Operand::Register(to_register), _)) => {
+            Ok(Instruction::Assign(to_register, value))
+        }
+        ((Operand::Register(to_register), _), (Operand::Label(label), _)) => Ok(
+            Instruction::AssignLabelLine(to_register, JumpTarget::new(&label)),
+        ),
+        ((Operand::Literal(_), _), (_, offset)) => Err(ParseError::at(
+            "second operand for assignment must be a register if the first operand is a literal",
+            offset,
+        )),
+        ((_, offset), _) => Err(ParseError::at(
+            "first operand for assignment must be a register or literal",
+            offset,
+        )),
+    }
+}
 
-    Ok(modify_register(
+/// Adds a register's value to another register's value.
+pub fn add(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, to_add) = parse_register_pair(operands, options, "add")?;
+
+    Ok(Instruction::Add(register, Operand::Register(to_add)))
+}
+
+/// Adds a literal value to a register's value.
+pub fn add_literal(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, to_add) = parse_register_and_literal(operands, options, "add literal")?;
+
+    Ok(Instruction::Add(register, Operand::Literal(to_add)))
+}
+
+/// Subtracts a register's value from another register's value.
+pub fn subtract(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, to_sub) = parse_register_pair(operands, options, "subtract")?;
+
+    Ok(Instruction::Subtract(register, Operand::Register(to_sub)))
+}
+
+/// Subtracts a literal value from a register's value.
+pub fn subtract_literal(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, to_sub) = parse_register_and_literal(operands, options, "subtract literal")?;
+
+    Ok(Instruction::Subtract(register, Operand::Literal(to_sub)))
+}
+
+/// Sets a register's value to the bitwise AND of its value and another register's value.
+pub fn bitwise_and(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, mask) = parse_register_pair(operands, options, "bitwise and")?;
+
+    Ok(Instruction::And(register, mask))
+}
+
+/// Sets a register's value to the bitwise OR of its value and another register's value.
+pub fn bitwise_or(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, mask) = parse_register_pair(operands, options, "bitwise or")?;
+
+    Ok(Instruction::Or(register, mask))
+}
+
+/// Sets a register's value to the bitwise XOR of its value and another register's value.
+pub fn bitwise_xor(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, mask) = parse_register_pair(operands, options, "bitwise xor")?;
+
+    Ok(Instruction::Xor(register, mask))
+}
+
+/// Sets a register's value to the smaller of its value and another register's value.
+pub fn min(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, other) = parse_register_pair(operands, options, "min")?;
+
+    Ok(Instruction::Min(register, other))
+}
+
+/// Sets a register's value to the larger of its value and another register's value.
+pub fn max(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, other) = parse_register_pair(operands, options, "max")?;
+
+    Ok(Instruction::Max(register, other))
+}
+
+/// Divides a register's value by another register's value or a literal value.
+pub fn divide(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, divisor) = parse_register_and_value(operands, options, "divide")?;
+
+    Ok(Instruction::Divide(register, divisor))
+}
+
+/// Sets a register's value to the remainder of dividing it by another register's value or a literal value.
+pub fn modulo(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, divisor) = parse_register_and_value(operands, options, "modulo")?;
+
+    Ok(Instruction::Modulo(register, divisor))
+}
+
+/// Sets a register's value to the floor of its square root.
+pub fn isqrt(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::SquareRoot(parse_register(
+        operands, options, "isqrt",
+    )?))
+}
+
+/// Shifts a register's value left by a number of bits specified by another register's value or a literal value.
+pub fn shift_left(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, amount) = parse_register_and_value(operands, options, "shift left")?;
+
+    Ok(Instruction::ShiftLeft(register, amount))
+}
+
+/// Shifts a register's value right by a number of bits specified by another register's value or a literal value.
+/// The sign of the value is preserved.
+pub fn shift_right(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, amount) = parse_register_and_value(operands, options, "shift right")?;
+
+    Ok(Instruction::ShiftRight(register, amount))
+}
+
+/// Exchanges the values of two registers.
+pub fn swap(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (first_register, second_register) = parse_register_pair(operands, options, "swap")?;
+
+    Ok(Instruction::Swap(first_register, second_register))
+}
+
+/// Reads a byte from stdin.
+pub fn read(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Read(parse_register(
+        operands, options, "read",
+    )?))
+}
+
+/// Reads a signed 16-bit integer from two bytes of stdin.
+pub fn read_word(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::ReadWord(parse_register(
         operands,
-        Transformation::Divide(2),
-        &mut context,
-    )?)
+        options,
+        "read word",
+    )?))
 }
 
-/// Sets a register's value to a random number between 0 and 9 (inclusive).
-pub fn randomize(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("randomize with operands: {}", operands);
+/// Reads a line from stdin.
+pub fn read_line(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::ReadLine(parse_register(
+        operands,
+        options,
+        "read line",
+    )?))
+}
 
-    let random_number = rand::thread_rng().gen_range(0, 10);
+/// Reads the rest of stdin and counts the bytes available.
+pub fn read_all(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::ReadAll(parse_register(
+        operands, options, "read all",
+    )?))
+}
 
-    Ok(modify_register(
+/// Reads an integer from stdin.
+pub fn read_int(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::ReadInt(parse_register(
         operands,
-        Transformation::Set(random_number),
-        &mut context,
-    )?)
+        options,
+        "read integer",
+    )?))
 }
 
-/// Sets a register's value to the value in another register or a literal value.
-pub fn assign(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("assignment with operands: {}", operands);
+/// Prints a register's value.
+pub fn print(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Print(parse_register(
+        operands, options, "print",
+    )?))
+}
 
-    let operands = parse_operands(operands)?;
-    // should be either a register followed by a register or literal, or a literal followed by a register
-    if operands.len() != 2 {
-        return Err(RuntimeError::new(
-            "wrong number of operands for assignment",
-            context,
-        ));
+/// Prints a register's value as a signed hexadecimal number.
+pub fn print_hex(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::PrintHex(parse_register(
+        operands,
+        options,
+        "print hexadecimal",
+    )?))
+}
+
+/// Prints the values of one or more registers, in order.
+pub fn print_all(operands: &str, options: &ParseOptions) -> ParseResult {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, operands.len())?;
+    if operands.is_empty() {
+        return Err("wrong number of operands for print all".into());
     }
 
-    match &operands[0] {
-        Operand::Register(to_register) => {
-            let new_value = match &operands[1] {
-                Operand::Register(name) => get_register_value(name, context)?,
-                Operand::Literal(val) => *val,
-                _ => return Err(RuntimeError::new(
-                    "second operand for assignment must be a register or literal",
-                    context,
-                ))
-            };
+    operands
+        .into_iter()
+        .map(|operand| match operand {
+            (Operand::Register(name), _) => Ok(name),
+            (_, offset) => Err(ParseError::at(
+                "operands for print all must be registers",
+                offset,
+            )),
+        })
+        .collect::<Result<Vec<String>, ParseError>>()
+        .map(Instruction::PrintAll)
+}
 
-            Ok(modify_register(
-                to_register,
-                Transformation::Set(new_value),
-                &mut context,
-            )?)
-        },
-        Operand::Literal(new_value) => {
-            match &operands[1] {
-                Operand::Register(to_register) => {
-                    Ok(modify_register(
-                        to_register,
-                        Transformation::Set(*new_value),
-                        &mut context,
-                    )?)
-                },
-                _ => Err(RuntimeError::new(
-                    "second operand for assignment must be a register if the first operand is a literal",
-                    context,
-                ))
-            }
-        },
-        _ => Err(RuntimeError::new(
-            "first operand for assignment must be a register or literal",
-            context,
-        ))
+/// Does nothing.
+pub fn noop(operands: &str, _options: &ParseOptions) -> ParseResult {
+    if !operands.is_empty() {
+        return Err("wrong number of operands for no-op".into());
+    }
+
+    Ok(Instruction::Noop)
+}
+
+/// Stops the program.
+pub fn halt(operands: &str, _options: &ParseOptions) -> ParseResult {
+    if !operands.is_empty() {
+        return Err("wrong number of operands for halt".into());
     }
+
+    Ok(Instruction::Halt)
 }
 
-/// Adds a register's value to another register's value.
-pub fn add(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("add with operands: {}", operands);
+/// Jumps to a label.
+pub fn jump(operands: &str, _options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Jump(JumpTarget::new(operands)))
+}
 
-    let operands = parse_operands(operands)?;
-    // should be a register followed by a register
-    if operands.len() != 2 {
-        return Err(RuntimeError::new(
-            "wrong number of operands for add",
-            context,
-        ));
+/// Jumps to the label at the index in the program's labels given by a register's value.
+/// Labels are indexed in the order they are defined in, starting at 0.
+pub fn jump_indirect(operands: &str, options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::JumpIndirect(parse_register(
+        operands,
+        options,
+        "jump indirect",
+    )?))
+}
+
+/// Jumps to a label, remembering where to return to.
+pub fn call(operands: &str, _options: &ParseOptions) -> ParseResult {
+    Ok(Instruction::Call(JumpTarget::new(operands)))
+}
+
+/// Returns to the line after the most recent call.
+pub fn ret(operands: &str, _options: &ParseOptions) -> ParseResult {
+    if !operands.is_empty() {
+        return Err("wrong number of operands for return".into());
     }
 
-    let register = match &operands[0] {
-        Operand::Register(name) => name,
-        _ => {
-            return Err(RuntimeError::new(
-                "first operand for add must be a register",
-                context,
-            ))
-        }
-    };
+    Ok(Instruction::Return)
+}
 
-    let to_add = match &operands[1] {
-        Operand::Register(name) => get_register_value(name, context)?,
-        _ => {
-            return Err(RuntimeError::new(
-                "second operand for add must be a register",
-                context,
-            ))
+/// Jumps to a label if a register's value is 0.
+pub fn jump_if_zero(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, target) = parse_register_and_label(operands, options, "jump if zero")?;
+
+    Ok(Instruction::JumpIfZero(register, target))
+}
+
+/// Jumps to a label if a register's value is not 0.
+pub fn jump_if_not_zero(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, target) = parse_register_and_label(operands, options, "jump if not zero")?;
+
+    Ok(Instruction::JumpIfNotZero(register, target))
+}
+
+/// Jumps to a label if a register's value is negative.
+pub fn jump_if_neg(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, target) = parse_register_and_label(operands, options, "jump if negative")?;
+
+    Ok(Instruction::JumpIfNegative(register, target))
+}
+
+/// Jumps to a label if a register's value is positive.
+pub fn jump_if_pos(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, target) = parse_register_and_label(operands, options, "jump if positive")?;
+
+    Ok(Instruction::JumpIfPositive(register, target))
+}
+
+/// Jumps to a label if a register's value is equal to another register's value or a literal value.
+pub fn jump_if_equal(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (register, value, target) =
+        parse_register_value_and_label(operands, options, "jump if equal")?;
+
+    Ok(Instruction::JumpIfEqual(register, value, target))
+}
+
+/// Jumps to a label if a register's value is greater than another register's value.
+pub fn jump_if_greater(operands: &str, options: &ParseOptions) -> ParseResult {
+    let (first_register, second_register, target) =
+        parse_register_pair_and_label(operands, options, "jump if greater")?;
+
+    Ok(Instruction::JumpIfGreater(
+        first_register,
+        second_register,
+        target,
+    ))
+}
+
+/// A parsed line of source code.
+#[derive(Debug)]
+pub enum Instruction {
+    /// Defines a label with the provided name. Does nothing when executed.
+    Label(String),
+    /// Defines a constant. Does nothing when executed.
+    DefineConstant,
+    /// Increments the value in a register.
+    Increment(String),
+    /// Decrements the value in a register.
+    Decrement(String),
+    /// Multiplies the value in a register by -1.
+    Negate(String),
+    /// Flips all the bits of the value in a register.
+    Complement(String),
+    /// Multiplies the value in a register by 2.
+    Double(String),
+    /// Divides the value in a register by 2.
+    Halve(String),
+    /// Shifts the value in a register left by the value of an operand.
+    ShiftLeft(String, Operand),
+    /// Shifts the value in a register right by the value of an operand.
+    ShiftRight(String, Operand),
+    /// Sets the value in a register to its square root.
+    SquareRoot(String),
+    /// Sets the value in a register to a random number between 0 and an upper bound (inclusive).
+    Randomize(String, i32),
+    /// Sets the value in a register to the value of an operand.
+    Assign(String, Operand),
+    /// Sets the value in a register to the 1-indexed line number a label is defined on, for use with `JumpIndirect`.
+    AssignLabelLine(String, JumpTarget),
+    /// Exchanges the values in two registers.
+    Swap(String, String),
+    /// Adds the value of an operand to the value in a register.
+    Add(String, Operand),
+    /// Subtracts the value of an operand from the value in a register.
+    Subtract(String, Operand),
+    /// Sets the value in the first register to the smaller of the values in the two registers.
+    Min(String, String),
+    /// Sets the value in the first register to the larger of the values in the two registers.
+    Max(String, String),
+    /// Divides the value in a register by the value of an operand.
+    Divide(String, Operand),
+    /// Sets the value in a register to the remainder of dividing it by the value of an operand.
+    Modulo(String, Operand),
+    /// Sets the value in the first register to the bitwise AND of the values in the two registers.
+    And(String, String),
+    /// Sets the value in the first register to the bitwise OR of the values in the two registers.
+    Or(String, String),
+    /// Sets the value in the first register to the bitwise XOR of the values in the two registers.
+    Xor(String, String),
+    /// Reads a byte from stdin into a register.
+    Read(String),
+    /// Reads two bytes from stdin into a register as a big-endian signed 16-bit integer.
+    ReadWord(String),
+    /// Reads a line from stdin, storing its first byte in a register and buffering the rest for later reads.
+    ReadLine(String),
+    /// Reads a whitespace-delimited base-10 integer from stdin into a register.
+    ReadInt(String),
+    /// Reads the rest of stdin, buffering it for later reads and storing the number of bytes left to read in a register.
+    ReadAll(String),
+    /// Prints the value in a register.
+    Print(String),
+    /// Prints the values in several registers, in order.
+    PrintAll(Vec<String>),
+    /// Prints the value in a register as a signed hexadecimal number, like `0x1f` or `-0xa`.
+    PrintHex(String),
+    /// Does nothing.
+    Noop,
+    /// Stops the program.
+    Halt,
+    /// Jumps to a label.
+    Jump(JumpTarget),
+    /// Jumps to the label defined on the 1-indexed line number given by the value in a register.
+    JumpIndirect(String),
+    /// Jumps to a label, remembering where to return to.
+    Call(JumpTarget),
+    /// Returns to the line after the most recent call.
+    Return,
+    /// Jumps to a label if the value in a register is 0.
+    JumpIfZero(String, JumpTarget),
+    /// Jumps to a label if the value in a register is not 0.
+    JumpIfNotZero(String, JumpTarget),
+    /// Jumps to a label if the value in a register is negative.
+    JumpIfNegative(String, JumpTarget),
+    /// Jumps to a label if the value in a register is positive.
+    JumpIfPositive(String, JumpTarget),
+    /// Jumps to a label if the value in a register is equal to the value of an operand.
+    JumpIfEqual(String, Operand, JumpTarget),
+    /// Jumps to a label if the value in the first register is greater than the value in the second register.
+    JumpIfGreater(String, String, JumpTarget),
+}
+
+impl Instruction {
+    /// Gets the jump target of this instruction, if it has one.
+    pub fn jump_target(&self) -> Option<&JumpTarget> {
+        match self {
+            Instruction::Jump(target)
+            | Instruction::Call(target)
+            | Instruction::JumpIfZero(_, target)
+            | Instruction::JumpIfNotZero(_, target)
+            | Instruction::JumpIfNegative(_, target)
+            | Instruction::JumpIfPositive(_, target)
+            | Instruction::JumpIfEqual(_, _, target)
+            | Instruction::JumpIfGreater(_, _, target) => Some(target),
+            _ => None,
         }
-    };
+    }
+
+    /// Determines whether execution never continues to the line after this instruction.
+    pub fn always_jumps(&self) -> bool {
+        matches!(
+            self,
+            Instruction::Jump(_)
+                | Instruction::JumpIndirect(_)
+                | Instruction::Return
+                | Instruction::Halt
+        )
+    }
 
-    Ok(modify_register(
-        register,
-        Transformation::Add(to_add),
-        &mut context,
-    )?)
+    /// Gets the jump target of this instruction mutably, if it has one.
+    /// This also includes the label of an `AssignLabelLine`, so it's resolved along with the jump targets.
+    pub fn jump_target_mut(&mut self) -> Option<&mut JumpTarget> {
+        match self {
+            Instruction::Jump(target)
+            | Instruction::AssignLabelLine(_, target)
+            | Instruction::Call(target)
+            | Instruction::JumpIfZero(_, target)
+            | Instruction::JumpIfNotZero(_, target)
+            | Instruction::JumpIfNegative(_, target)
+            | Instruction::JumpIfPositive(_, target)
+            | Instruction::JumpIfEqual(_, _, target)
+            | Instruction::JumpIfGreater(_, _, target) => Some(target),
+            _ => None,
+        }
+    }
 }
 
-/// Subtracts a register's value from another register's value.
-pub fn subtract(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("subtract with operands: {}", operands);
+/// The label a jump goes to.
+#[derive(Debug)]
+pub struct JumpTarget {
+    /// The name of the label.
+    pub label: String,
+    /// The 0-indexed line number the label is defined on.
+    /// This is filled in once all the labels in the program are known.
+    pub line_number: usize,
+}
 
-    let operands = parse_operands(operands)?;
-    // should be a register followed by a register
-    if operands.len() != 2 {
-        return Err(RuntimeError::new(
-            "wrong number of operands for subtract",
-            context,
-        ));
+impl JumpTarget {
+    /// Creates a new `JumpTarget` for the label with the provided name.
+    fn new(label: &str) -> JumpTarget {
+        JumpTarget {
+            label: label.to_string(),
+            line_number: 0,
+        }
     }
+}
 
-    let register = match &operands[0] {
-        Operand::Register(name) => name,
-        _ => {
-            return Err(RuntimeError::new(
-                "first operand for subtract must be a register",
-                context,
-            ))
+/// Executes an instruction.
+pub fn execute(instruction: &Instruction, context: &mut Context) -> OpResult {
+    debug!("executing instruction: {:?}", instruction);
+
+    match instruction {
+        Instruction::Label(_) | Instruction::DefineConstant | Instruction::Noop => Ok(()),
+        Instruction::Increment(register) => {
+            modify_register(register, Transformation::Add(1), context)
+        }
+        Instruction::Decrement(register) => {
+            modify_register(register, Transformation::Add(-1), context)
+        }
+        Instruction::Negate(register) => {
+            modify_register(register, Transformation::Multiply(-1), context)
+        }
+        Instruction::Complement(register) => {
+            modify_register(register, Transformation::Not, context)
+        }
+        Instruction::Double(register) => {
+            modify_register(register, Transformation::Multiply(2), context)
+        }
+        Instruction::Halve(register) => {
+            modify_register(register, Transformation::Divide(2), context)
+        }
+        Instruction::ShiftLeft(register, amount) => {
+            let amount = get_operand_value(amount, context)?;
+            modify_register(register, Transformation::ShiftLeft(amount), context)
+        }
+        Instruction::ShiftRight(register, amount) => {
+            let amount = get_operand_value(amount, context)?;
+            modify_register(register, Transformation::ShiftRight(amount), context)
+        }
+        Instruction::SquareRoot(register) => {
+            modify_register(register, Transformation::SquareRoot, context)
+        }
+        Instruction::Randomize(register, upper_bound) => {
+            let random_number = context.rng.sample(Uniform::new_inclusive(0, upper_bound));
+            modify_register(register, Transformation::Set(random_number), context)
         }
+        Instruction::Assign(register, value) => {
+            let new_value = get_operand_value(value, context)?;
+            modify_register(register, Transformation::Set(new_value), context)
+        }
+        Instruction::AssignLabelLine(register, target) => {
+            let line_number = target.line_number as i32 + 1;
+            modify_register(register, Transformation::Set(line_number), context)
+        }
+        Instruction::Swap(first_register, second_register) => {
+            let first_value = get_register_value(first_register, context)?;
+            let second_value = get_register_value(second_register, context)?;
+            modify_register(first_register, Transformation::Set(second_value), context)?;
+            modify_register(second_register, Transformation::Set(first_value), context)
+        }
+        Instruction::Add(register, other) => {
+            let to_add = get_operand_value(other, context)?;
+            modify_register(register, Transformation::Add(to_add), context)
+        }
+        Instruction::Subtract(register, other) => {
+            let to_sub = get_operand_value(other, context)?;
+            modify_register(register, Transformation::Subtract(to_sub), context)
+        }
+        Instruction::Min(register, other) => {
+            let other = get_register_value(other, context)?;
+            modify_register(register, Transformation::Min(other), context)
+        }
+        Instruction::Max(register, other) => {
+            let other = get_register_value(other, context)?;
+            modify_register(register, Transformation::Max(other), context)
+        }
+        Instruction::Divide(register, divisor) => {
+            let divisor = get_operand_value(divisor, context)?;
+            modify_register(register, Transformation::Divide(divisor), context)
+        }
+        Instruction::Modulo(register, divisor) => {
+            let divisor = get_operand_value(divisor, context)?;
+            modify_register(register, Transformation::Modulo(divisor), context)
+        }
+        Instruction::And(register, other) => {
+            let mask = get_register_value(other, context)?;
+            modify_register(register, Transformation::And(mask), context)
+        }
+        Instruction::Or(register, other) => {
+            let mask = get_register_value(other, context)?;
+            modify_register(register, Transformation::Or(mask), context)
+        }
+        Instruction::Xor(register, other) => {
+            let mask = get_register_value(other, context)?;
+            modify_register(register, Transformation::Xor(mask), context)
+        }
+        Instruction::Read(register) => execute_read(register, context),
+        Instruction::ReadWord(register) => execute_read_word(register, context),
+        Instruction::ReadLine(register) => execute_read_line(register, context),
+        Instruction::ReadAll(register) => execute_read_all(register, context),
+        Instruction::ReadInt(register) => execute_read_int(register, context),
+        Instruction::Print(register) => execute_print(register, context),
+        Instruction::PrintAll(registers) => registers
+            .iter()
+            .try_for_each(|register| execute_print(register, context)),
+        Instruction::PrintHex(register) => execute_print_hex(register, context),
+        Instruction::Halt => {
+            context.current_line_number = context.program.instructions.len();
+            Ok(())
+        }
+        Instruction::Jump(target) => {
+            jump_to_label(target, context);
+            Ok(())
+        }
+        Instruction::JumpIndirect(register) => execute_jump_indirect(register, context),
+        Instruction::Call(target) => {
+            context.call_stack.push(context.current_line_number);
+            jump_to_label(target, context);
+            Ok(())
+        }
+        Instruction::Return => match context.call_stack.pop() {
+            Some(x) => {
+                context.current_line_number = x;
+                context.jumps += 1;
+                Ok(())
+            }
+            None => Err(Error::new(
+                "cannot return without a matching call",
+                context.current_line_number,
+            )),
+        },
+        Instruction::JumpIfZero(register, target) => {
+            let value = get_register_value(register, context)?;
+            jump_to_label_if(value == 0, target, context)
+        }
+        Instruction::JumpIfNotZero(register, target) => {
+            let value = get_register_value(register, context)?;
+            jump_to_label_if(value != 0, target, context)
+        }
+        Instruction::JumpIfNegative(register, target) => {
+            let value = get_register_value(register, context)?;
+            jump_to_label_if(value < 0, target, context)
+        }
+        Instruction::JumpIfPositive(register, target) => {
+            let value = get_register_value(register, context)?;
+            jump_to_label_if(value > 0, target, context)
+        }
+        Instruction::JumpIfEqual(register, value, target) => {
+            let first_value = get_register_value(register, context)?;
+            let second_value = get_operand_value(value, context)?;
+            jump_to_label_if(first_value == second_value, target, context)
+        }
+        Instruction::JumpIfGreater(first_register, second_register, target) => {
+            let first_value = get_register_value(first_register, context)?;
+            let second_value = get_register_value(second_register, context)?;
+            jump_to_label_if(first_value > second_value, target, context)
+        }
+    }
+}
+
+/// Stores the next integer passed in ahead of time in the register with the provided name, if there are any left.
+/// Reads use these integers before anything from stdin.
+///
+/// Returns `None` if there are no integers left, in which case the value should be read from stdin.
+fn read_arg(register: &str, context: &mut Context) -> Option<OpResult> {
+    let x = context.args.pop_front()?;
+    Some(modify_register(register, Transformation::Set(x), context))
+}
+
+/// Reads a byte from stdin into the register with the provided name.
+/// Bytes left over from a previous line read are used before reading any more from stdin.
+fn execute_read(register: &str, context: &mut Context) -> OpResult {
+    if let Some(result) = read_arg(register, context) {
+        return result;
+    }
+
+    let new_value = match read_byte(context)? {
+        Some(b) => b as i32,
+        None => context.eof,
     };
 
-    let to_sub = match &operands[1] {
-        Operand::Register(name) => get_register_value(name, context)?,
-        _ => {
-            return Err(RuntimeError::new(
-                "second operand for subtract must be a register",
-                context,
-            ))
-        }
+    modify_register(register, Transformation::Set(new_value), context)
+}
+
+/// Reads two bytes from stdin and stores them in the register with the provided name as a big-endian signed 16-bit integer.
+/// If EOF is encountered before both bytes are read, the register is set to `context.eof`.
+fn execute_read_word(register: &str, context: &mut Context) -> OpResult {
+    if let Some(result) = read_arg(register, context) {
+        return result;
+    }
+
+    let new_value = match read_byte(context)? {
+        Some(high) => match read_byte(context)? {
+            Some(low) => i16::from_be_bytes([high, low]) as i32,
+            None => context.eof,
+        },
+        None => context.eof,
     };
 
-    Ok(modify_register(
-        register,
-        Transformation::Add(-to_sub),
-        &mut context,
-    )?)
+    modify_register(register, Transformation::Set(new_value), context)
 }
 
-/// Reads a byte from stdin.
-pub fn read(operands: &str, mut context: &mut Context) -> OpResult {
-    debug!("read with operands: {}", operands);
-
-    let new_value = match std::io::stdin().bytes().next() {
-        Some(b) => match b {
-            Ok(b) => b as i32,
-            Err(e) => {
-                return Err(RuntimeError::new(
-                    &format!("error reading from stdin: {}", e),
-                    context,
+/// Reads a single byte, using bytes left over from a previous line read before reading any more from stdin.
+///
+/// Returns the byte, or `None` if EOF was encountered.
+fn read_byte(context: &mut Context) -> Result<Option<u8>, Error> {
+    if let Some(b) = context.input_buffer.pop_front() {
+        return Ok(Some(b));
+    }
+
+    // make sure any prompts are visible before waiting for input
+    context.flush_output()?;
+
+    match (&mut context.input).bytes().next() {
+        Some(Ok(b)) => {
+            context.bytes_read += 1;
+            Ok(Some(b))
+        }
+        Some(Err(e)) => Err(Error::new(
+            &format!("error reading from stdin: {}", e),
+            context.current_line_number,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Reads a whitespace-delimited base-10 integer from stdin into the register with the provided name.
+/// If EOF is encountered before any digits are read, the register is set to `context.eof`.
+fn execute_read_int(register: &str, context: &mut Context) -> OpResult {
+    if let Some(result) = read_arg(register, context) {
+        return result;
+    }
+    // make sure any prompts are visible before waiting for input
+    context.flush_output()?;
+
+    let token = read_token(
+        &mut context.input_buffer,
+        &mut context.input,
+        &mut context.bytes_read,
+    )
+    .map_err(|e| {
+        Error::new(
+            &format!("error reading from stdin: {}", e),
+            context.current_line_number,
+        )
+    })?;
+    let new_value = if token.is_empty() {
+        context.eof
+    } else {
+        match token.parse() {
+            Ok(x) => x,
+            Err(_) => {
+                return Err(Error::new(
+                    &format!("invalid integer: {}", token),
+                    context.current_line_number,
                 ))
             }
-        },
-        None => -1,
+        }
     };
 
-    Ok(modify_register(
-        operands,
-        Transformation::Set(new_value),
-        &mut context,
-    )?)
+    modify_register(register, Transformation::Set(new_value), context)
 }
 
-/// Prints a register's value.
-pub fn print(operands: &str, context: &mut Context) -> OpResult {
-    debug!("print with operands: {}", operands);
+/// Reads a whitespace-delimited token from `input`, leaving the whitespace after it unread.
+/// Bytes in `input_buffer` are used before reading any more from `input`.
+/// Returns an empty string if EOF is encountered before the token starts.
+///
+/// # Arguments
+/// * `input_buffer`: Bytes that have been read from `input` already but not used yet.
+/// * `input`: Where to read more bytes from.
+/// * `bytes_read`: The number of bytes read from `input` so far, which is increased by the number of bytes this reads from it.
+fn read_token(
+    input_buffer: &mut VecDeque<u8>,
+    input: &mut dyn BufRead,
+    bytes_read: &mut u64,
+) -> std::io::Result<String> {
+    let mut token = Vec::new();
+    while let Some(&b) = input_buffer.front() {
+        if b.is_ascii_whitespace() {
+            if !token.is_empty() {
+                return Ok(String::from_utf8_lossy(&token).into_owned());
+            }
+        } else {
+            token.push(b);
+        }
+        input_buffer.pop_front();
+    }
 
-    let operands = parse_operands(operands)?;
-    // should be a register
-    if operands.len() != 1 {
-        return Err(RuntimeError::new(
-            "wrong number of operands for print",
-            context,
-        ));
+    loop {
+        let buf = input.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        let whitespace_start = buf.iter().position(|b| b.is_ascii_whitespace());
+        let token_end = match whitespace_start {
+            Some(0) if token.is_empty() => {
+                input.consume(1);
+                *bytes_read += 1;
+                continue;
+            }
+            Some(x) => x,
+            None => buf.len(),
+        };
+        token.extend_from_slice(&buf[..token_end]);
+        input.consume(token_end);
+        *bytes_read += token_end as u64;
+        if whitespace_start.is_some() {
+            break;
+        }
     }
+    Ok(String::from_utf8_lossy(&token).into_owned())
+}
 
-    let register = match &operands[0] {
-        Operand::Register(name) => name,
-        _ => {
-            return Err(RuntimeError::new(
-                "operand for print must be a register",
-                context,
-            ))
+/// Reads a line from stdin, stores its first byte in the register with the provided name, and keeps the rest of the line for later reads.
+/// If the line that's already been partially read hasn't been used up yet, no more is read from stdin.
+/// If EOF is encountered before a newline, the line ends there; if there's nothing left to read at all, the register is set to `context.eof`.
+fn execute_read_line(register: &str, context: &mut Context) -> OpResult {
+    if let Some(result) = read_arg(register, context) {
+        return result;
+    }
+    if !context.input_buffer.contains(&b'\n') {
+        // make sure any prompts are visible before waiting for input
+        context.flush_output()?;
+
+        let mut line = Vec::new();
+        if let Err(e) = context.input.read_until(b'\n', &mut line) {
+            return Err(Error::new(
+                &format!("error reading from stdin: {}", e),
+                context.current_line_number,
+            ));
         }
+        context.bytes_read += line.len() as u64;
+        context.input_buffer.extend(line);
+    }
+
+    let new_value = match context.input_buffer.pop_front() {
+        Some(b) => b as i32,
+        None => context.eof,
     };
 
-    let to_print = get_register_value(register, context)?;
-    if to_print < 0 {
-        return Err(RuntimeError::new(
-            &format!(
-                "{} does not correspond to a valid UTF-8 character",
-                to_print
-            ),
-            context,
+    modify_register(register, Transformation::Set(new_value), context)
+}
+
+/// Reads everything left in stdin into the input buffer, and stores the number of bytes in the buffer in the register with the provided name.
+/// Later reads use the buffered bytes, so nothing is lost.
+fn execute_read_all(register: &str, context: &mut Context) -> OpResult {
+    // make sure any prompts are visible before waiting for input
+    context.flush_output()?;
+
+    let mut rest = Vec::new();
+    if let Err(e) = context.input.read_to_end(&mut rest) {
+        return Err(Error::new(
+            &format!("error reading from stdin: {}", e),
+            context.current_line_number,
         ));
     }
+    context.bytes_read += rest.len() as u64;
+    context.input_buffer.extend(rest);
+
+    let new_value = match std::convert::TryFrom::try_from(context.input_buffer.len()) {
+        Ok(x) => x,
+        Err(_) => {
+            return Err(Error::new(
+                "too much input to count",
+                context.current_line_number,
+            ))
+        }
+    };
+    modify_register(register, Transformation::Set(new_value), context)
+}
 
-    match std::char::from_u32(to_print as u32) {
+/// Prints the value of the register with the provided name.
+fn execute_print(register: &str, context: &mut Context) -> OpResult {
+    let to_print = get_register_value(register, context)?;
+    // negative values, UTF-16 surrogates (0xD800 to 0xDFFF), and values above 0x10FFFF aren't valid characters
+    match std::convert::TryFrom::try_from(to_print)
+        .ok()
+        .and_then(std::char::from_u32)
+    {
         Some(c) => {
-            print!("{}", c);
-            std::io::stdout().flush().unwrap();
+            if let Err(e) = write!(context.output, "{}", c) {
+                return Err(Error::new(
+                    &format!("error writing to stdout: {}", e),
+                    context.current_line_number,
+                ));
+            }
+            context.last_printed = Some(c);
+            context.bytes_printed += c.len_utf8() as u64;
         }
         _ => {
-            return Err(RuntimeError::new(
+            return Err(Error::new(
                 &format!(
                     "{} does not correspond to a valid UTF-8 character",
                     to_print
                 ),
-                context,
+                context.current_line_number,
             ))
         }
     }
@@ -290,96 +1047,109 @@ pub fn print(operands: &str, context: &mut Context) -> OpResult {
     Ok(())
 }
 
-/// Jumps to a label.
-pub fn jump(operands: &str, context: &mut Context) -> OpResult {
-    debug!("jump with operands: {}", operands);
-
-    jump_to_label(operands, context)
-}
-
-/// Jumps to a label if a register's value is 0.
-pub fn jump_if_zero(operands: &str, context: &mut Context) -> OpResult {
-    debug!("jump if zero with operands: {}", operands);
-
-    let operands = parse_operands(operands)?;
-    // should be a register and a label
-    if operands.len() != 2 {
-        return Err(RuntimeError::new(
-            "wrong number of operands for jump if zero",
-            context,
+/// Prints the value of the register with the provided name as a signed hexadecimal number, like `0x1f` or `-0xa`.
+fn execute_print_hex(register: &str, context: &mut Context) -> OpResult {
+    let value = get_register_value(register, context)?;
+    let sign = if value < 0 { "-" } else { "" };
+    let to_print = format!("{}0x{:x}", sign, value.unsigned_abs());
+    if let Err(e) = write!(context.output, "{}", to_print) {
+        return Err(Error::new(
+            &format!("error writing to stdout: {}", e),
+            context.current_line_number,
         ));
     }
+    context.last_printed = to_print.chars().last();
+    context.bytes_printed += to_print.len() as u64;
 
-    let register = match &operands[0] {
-        Operand::Register(name) => name,
-        _ => {
-            return Err(RuntimeError::new(
-                "first operand for jump if zero must be a register",
-                context,
-            ))
-        }
-    };
+    Ok(())
+}
 
-    let label = match &operands[1] {
-        Operand::Label(name) => name,
-        _ => {
-            return Err(RuntimeError::new(
-                "second operand for jump if zero must be a label",
-                context,
+/// Jumps to the label defined on the 1-indexed line number given by the value of the register with the provided name.
+fn execute_jump_indirect(register: &str, context: &mut Context) -> OpResult {
+    let value = get_register_value(register, context)?;
+
+    let line_number = usize::try_from(value)
+        .ok()
+        .and_then(|x| x.checked_sub(1))
+        .filter(|x| {
+            context
+                .program
+                .label_line_numbers()
+                .binary_search(x)
+                .is_ok()
+        });
+    match line_number {
+        Some(x) => {
+            context.current_line_number = x;
+            context.jumps += 1;
+        }
+        None => {
+            return Err(Error::new(
+                &format!("no label on line {}", value),
+                context.current_line_number,
             ))
         }
-    };
-
-    if get_register_value(register, context)? == 0 {
-        jump_to_label(label, context)?;
     }
 
     Ok(())
 }
 
-/// Jumps to a label if a register's value is negative.
-pub fn jump_if_neg(operands: &str, context: &mut Context) -> OpResult {
-    debug!("jump if negative with operands: {}", operands);
+/// An error encountered while parsing the operands of an operation.
+#[derive(Debug)]
+pub struct ParseError {
+    /// A message describing why the operands could not be parsed.
+    pub message: String,
+    /// The 0-indexed offset in the operands of the operand that caused the error, if the error is about a particular operand.
+    pub offset: Option<usize>,
+}
 
-    let operands = parse_operands(operands)?;
-    // should be a register and a label
-    if operands.len() != 2 {
-        return Err(RuntimeError::new(
-            "wrong number of operands for jump if negative",
-            context,
-        ));
+impl ParseError {
+    /// Creates a new `ParseError` about the operand at the provided offset.
+    ///
+    /// # Arguments
+    /// * `message`: A message describing the error.
+    /// * `offset`: The 0-indexed offset in the operands of the operand that caused the error.
+    fn at(message: &str, offset: usize) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+            offset: Some(offset),
+        }
     }
 
-    let register = match &operands[0] {
-        Operand::Register(name) => name,
-        _ => {
-            return Err(RuntimeError::new(
-                "first operand for jump if negative must be a register",
-                context,
-            ))
+    /// Converts this error into an `Error` for the line it was encountered on.
+    ///
+    /// # Arguments
+    /// * `line_number`: The 0-indexed line number of the line the operands are from.
+    /// * `operands_start`: The 0-indexed column in the line the operands start at.
+    pub fn into_error(self, line_number: usize, operands_start: usize) -> Error {
+        match self.offset {
+            Some(offset) => Error::with_column(&self.message, line_number, operands_start + offset),
+            None => Error::new(&self.message, line_number),
         }
-    };
+    }
+}
 
-    let label = match &operands[1] {
-        Operand::Label(name) => name,
-        _ => {
-            return Err(RuntimeError::new(
-                "second operand for jump if negative must be a label",
-                context,
-            ))
+impl From<&str> for ParseError {
+    fn from(message: &str) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+            offset: None,
         }
-    };
-
-    if get_register_value(register, context)? < 0 {
-        jump_to_label(label, context)?;
     }
+}
 
-    Ok(())
+impl From<String> for ParseError {
+    fn from(message: String) -> ParseError {
+        ParseError {
+            message,
+            offset: None,
+        }
+    }
 }
 
 /// An operand for an operation.
 #[derive(Debug)]
-enum Operand {
+pub enum Operand {
     /// The name of a register.
     Register(String),
     /// A literal value.
@@ -388,59 +1158,95 @@ enum Operand {
     Label(String),
 }
 
-/// Parses a string of operands to a list of `Operand`s.
-fn parse_operands(operands: &str) -> Result<Vec<Operand>, RuntimeError> {
+/// Parses a string of operands to a list of `Operand`s, each paired with the 0-indexed offset in `operands` it starts at.
+pub fn parse_operands(
+    operands: &str,
+    options: &ParseOptions,
+) -> Result<Vec<(Operand, usize)>, ParseError> {
     let mut remaining_operands = operands.to_string();
     let mut parsed_operands = Vec::new();
     'outer: while !remaining_operands.is_empty() {
         trace!("remaining operands: {}", remaining_operands);
-        for register_name in REGISTER_NAMES.iter() {
-            if remaining_operands.starts_with(register_name) {
-                parsed_operands.push(Operand::Register(register_name.to_string()));
-                let regex = Regex::new(&format!(
-                    "^{}({})?",
-                    register_name,
-                    OPERAND_CONNECTORS.join("|")
-                ))
-                .unwrap();
-                remaining_operands = regex.replace(&remaining_operands, "").to_string();
+        let offset = operands.len() - remaining_operands.len();
+        for register_name in &options.registers {
+            if remaining_operands.starts_with(register_name.as_str()) {
+                parsed_operands.push((Operand::Register(register_name.clone()), offset));
+                remaining_operands = OPERAND_CONNECTOR_PATTERN
+                    .replace(&remaining_operands[register_name.len()..], "")
+                    .to_string();
                 continue 'outer;
             }
         }
 
-        for (literal_name, _) in LITERALS.iter() {
-            if remaining_operands.starts_with(literal_name) {
-                let parsed = parse_literal(&mut remaining_operands);
-                parsed_operands.push(Operand::Literal(parsed));
-                let regex = Regex::new(&format!("^({})?", OPERAND_CONNECTORS.join("|"))).unwrap();
-                remaining_operands = regex.replace(&remaining_operands, "").to_string();
+        let negative = remaining_operands.starts_with(NEGATIVE_LITERAL_PREFIX) && {
+            let negated = &remaining_operands[NEGATIVE_LITERAL_PREFIX.len()..];
+            options
+                .literals
+                .iter()
+                .any(|(literal_name, _)| negated.starts_with(literal_name.as_str()))
+                || find_constant(negated, options).is_some()
+        };
+        if negative {
+            remaining_operands.replace_range(..NEGATIVE_LITERAL_PREFIX.len(), "");
+        }
+
+        if let Some((constant_name, value)) = find_constant(&remaining_operands, options) {
+            let value = if negative { -value } else { value };
+            parsed_operands.push((Operand::Literal(value), offset));
+            remaining_operands = OPERAND_CONNECTOR_PATTERN
+                .replace(&remaining_operands[constant_name.len()..], "")
+                .to_string();
+            continue 'outer;
+        }
+
+        for (literal_name, _) in &options.literals {
+            if remaining_operands.starts_with(literal_name.as_str()) {
+                let parsed = parse_literal(&mut remaining_operands, options).ok_or_else(|| {
+                    ParseError::at("literal is too large to fit in a register", offset)
+                })?;
+                let parsed = if negative { -parsed } else { parsed };
+                parsed_operands.push((Operand::Literal(parsed), offset));
+                remaining_operands = OPERAND_CONNECTOR_PATTERN
+                    .replace(&remaining_operands, "")
+                    .to_string();
                 continue 'outer;
             }
         }
 
-        // didn't find any registers or literals, so it must be a label
-        parsed_operands.push(Operand::Label(remaining_operands));
+        // didn't find any registers, constants, or literals, so it must be a label
+        parsed_operands.push((Operand::Label(remaining_operands), offset));
         remaining_operands = "".to_string();
     }
     debug!("parsed operands: {:?}", parsed_operands);
     Ok(parsed_operands)
 }
 
+/// Finds the named constant at the start of a string of operands.
+/// If the names of more than one constant match, the longest one is used.
+///
+/// Returns the name and value of the constant, or `None` if the operands don't start with a constant.
+fn find_constant<'a>(operands: &str, options: &'a ParseOptions) -> Option<(&'a String, i32)> {
+    options
+        .constants
+        .iter()
+        .filter(|(name, _)| operands.starts_with(name.as_str()))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(name, value)| (name, *value))
+}
+
 /// Parses a literal value from a string of operands.
 /// The string representation of the literal will be removed from the provided string.
-fn parse_literal(operands: &mut String) -> i32 {
+///
+/// Returns the value of the literal, or `None` if it's too large to fit in a register.
+fn parse_literal(operands: &mut String, options: &ParseOptions) -> Option<i32> {
     let mut found_literals = Vec::new();
     'outer: while !operands.is_empty() {
-        for (literal_name, literal_value) in LITERALS.iter() {
-            if operands.starts_with(literal_name) {
+        for (literal_name, literal_value) in &options.literals {
+            if operands.starts_with(literal_name.as_str()) {
                 found_literals.push(*literal_value);
-                let regex = Regex::new(&format!(
-                    "^{}({})?",
-                    literal_name,
-                    LITERAL_CONNECTORS.join("|")
-                ))
-                .unwrap();
-                *operands = regex.replace(operands, "").to_string();
+                *operands = LITERAL_CONNECTOR_PATTERN
+                    .replace(&operands[literal_name.len()..], "")
+                    .to_string();
                 continue 'outer;
             }
         }
@@ -449,23 +1255,371 @@ fn parse_literal(operands: &mut String) -> i32 {
         break;
     }
 
-    let mut combined: i32 = 0;
-    let mut current_place = 1;
-    for x in found_literals.iter().rev() {
-        combined += (*x as i32) * current_place;
-        current_place *= 10;
+    // adding each digit after shifting the ones before it over a place means leading zeros can't overflow
+    found_literals.iter().try_fold(0_i32, |combined, x| {
+        combined
+            .checked_mul(options.radix as i32)?
+            .checked_add(*x as i32)
+    })
+}
+
+/// Checks that none of the first operands in a list are labels.
+/// Operands that aren't registers or literals are parsed as labels, so a label where one isn't allowed is most likely a misspelled register.
+///
+/// # Arguments
+/// * `operands`: The operands to check.
+/// * `count`: The number of operands at the start of the list that can't be labels.
+///
+/// Returns `Err(ParseError)` naming the unknown register if any of those operands is a label.
+fn reject_labels(operands: &[(Operand, usize)], count: usize) -> Result<(), ParseError> {
+    for (operand, offset) in operands.iter().take(count) {
+        if let Operand::Label(label) = operand {
+            // everything after an unknown register is parsed as part of the same label, so only report the part before the next operand
+            let name = OPERAND_CONNECTORS
+                .iter()
+                .filter_map(|connector| label.find(connector))
+                .min()
+                .map_or(label.as_str(), |end| &label[..end]);
+            return Err(ParseError::at(
+                &format!("unknown register: {}", name),
+                *offset,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parses a string of operands that should consist of a single register.
+///
+/// # Arguments
+/// * `operands`: The operands to parse.
+/// * `options`: Options that affect how the operands are parsed.
+/// * `operation_name`: The name of the operation the operands are for, used in error messages.
+///
+/// Returns the name of the register.
+fn parse_register(
+    operands: &str,
+    options: &ParseOptions,
+    operation_name: &str,
+) -> Result<String, ParseError> {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 1)?;
+    if operands.len() != 1 {
+        return Err(format!("wrong number of operands for {}", operation_name).into());
+    }
+
+    match operands.into_iter().next().unwrap() {
+        (Operand::Register(name), _) => Ok(name),
+        (_, offset) => Err(ParseError::at(
+            &format!("operand for {} must be a register", operation_name),
+            offset,
+        )),
+    }
+}
+
+/// Parses a string of operands that should consist of a register followed by another register.
+///
+/// # Arguments
+/// * `operands`: The operands to parse.
+/// * `options`: Options that affect how the operands are parsed.
+/// * `operation_name`: The name of the operation the operands are for, used in error messages.
+///
+/// Returns the names of the two registers.
+fn parse_register_pair(
+    operands: &str,
+    options: &ParseOptions,
+    operation_name: &str,
+) -> Result<(String, String), ParseError> {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 2)?;
+    if operands.len() != 2 {
+        return Err(format!("wrong number of operands for {}", operation_name).into());
+    }
+
+    let mut operands = operands.into_iter();
+    let first_register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("first operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    let second_register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("second operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    Ok((first_register, second_register))
+}
+
+/// Parses a string of operands that should consist of a register followed by another register or a literal.
+///
+/// # Arguments
+/// * `operands`: The operands to parse.
+/// * `options`: Options that affect how the operands are parsed.
+/// * `operation_name`: The name of the operation the operands are for, used in error messages.
+///
+/// Returns the name of the register and the second operand.
+fn parse_register_and_value(
+    operands: &str,
+    options: &ParseOptions,
+    operation_name: &str,
+) -> Result<(String, Operand), ParseError> {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 2)?;
+    if operands.len() != 2 {
+        return Err(format!("wrong number of operands for {}", operation_name).into());
+    }
+
+    let mut operands = operands.into_iter();
+    let register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("first operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    let value = match operands.next().unwrap() {
+        (value @ Operand::Register(_), _) | (value @ Operand::Literal(_), _) => value,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!(
+                    "second operand for {} must be a register or literal",
+                    operation_name
+                ),
+                offset,
+            ))
+        }
+    };
+
+    Ok((register, value))
+}
+
+/// Parses a string of operands that should consist of a register followed by a literal.
+///
+/// # Arguments
+/// * `operands`: The operands to parse.
+/// * `options`: Options that affect how the operands are parsed.
+/// * `operation_name`: The name of the operation the operands are for, used in error messages.
+///
+/// Returns the name of the register and the value of the literal.
+fn parse_register_and_literal(
+    operands: &str,
+    options: &ParseOptions,
+    operation_name: &str,
+) -> Result<(String, i32), ParseError> {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 1)?;
+    if operands.len() != 2 {
+        return Err(format!("wrong number of operands for {}", operation_name).into());
+    }
+
+    let mut operands = operands.into_iter();
+    let register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("first operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    let value = match operands.next().unwrap() {
+        (Operand::Literal(value), _) => value,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("second operand for {} must be a literal", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    Ok((register, value))
+}
+
+/// Parses a string of operands that should consist of a register followed by a label.
+///
+/// # Arguments
+/// * `operands`: The operands to parse.
+/// * `options`: Options that affect how the operands are parsed.
+/// * `operation_name`: The name of the operation the operands are for, used in error messages.
+///
+/// Returns the name of the register and the label as a jump target.
+fn parse_register_and_label(
+    operands: &str,
+    options: &ParseOptions,
+    operation_name: &str,
+) -> Result<(String, JumpTarget), ParseError> {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 1)?;
+    if operands.len() != 2 {
+        return Err(format!("wrong number of operands for {}", operation_name).into());
     }
 
-    combined
+    let mut operands = operands.into_iter();
+    let register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("first operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    let target = match operands.next().unwrap() {
+        (Operand::Label(name), _) => JumpTarget::new(&name),
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("second operand for {} must be a label", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    Ok((register, target))
+}
+
+/// Parses a string of operands that should consist of a register, followed by another register or a literal, followed by a label.
+///
+/// # Arguments
+/// * `operands`: The operands to parse.
+/// * `options`: Options that affect how the operands are parsed.
+/// * `operation_name`: The name of the operation the operands are for, used in error messages.
+///
+/// Returns the name of the register, the second operand, and the label as a jump target.
+fn parse_register_value_and_label(
+    operands: &str,
+    options: &ParseOptions,
+    operation_name: &str,
+) -> Result<(String, Operand, JumpTarget), ParseError> {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 2)?;
+    if operands.len() != 3 {
+        return Err(format!("wrong number of operands for {}", operation_name).into());
+    }
+
+    let mut operands = operands.into_iter();
+    let register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("first operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    let value = match operands.next().unwrap() {
+        (value @ Operand::Register(_), _) | (value @ Operand::Literal(_), _) => value,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!(
+                    "second operand for {} must be a register or literal",
+                    operation_name
+                ),
+                offset,
+            ))
+        }
+    };
+
+    let target = match operands.next().unwrap() {
+        (Operand::Label(name), _) => JumpTarget::new(&name),
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("third operand for {} must be a label", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    Ok((register, value, target))
+}
+
+/// Parses a string of operands that should consist of a register, followed by another register, followed by a label.
+///
+/// # Arguments
+/// * `operands`: The operands to parse.
+/// * `options`: Options that affect how the operands are parsed.
+/// * `operation_name`: The name of the operation the operands are for, used in error messages.
+///
+/// Returns the names of the two registers and the label as a jump target.
+fn parse_register_pair_and_label(
+    operands: &str,
+    options: &ParseOptions,
+    operation_name: &str,
+) -> Result<(String, String, JumpTarget), ParseError> {
+    let operands = parse_operands(operands, options)?;
+    reject_labels(&operands, 2)?;
+    if operands.len() != 3 {
+        return Err(format!("wrong number of operands for {}", operation_name).into());
+    }
+
+    let mut operands = operands.into_iter();
+    let first_register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("first operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    let second_register = match operands.next().unwrap() {
+        (Operand::Register(name), _) => name,
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("second operand for {} must be a register", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    let target = match operands.next().unwrap() {
+        (Operand::Label(name), _) => JumpTarget::new(&name),
+        (_, offset) => {
+            return Err(ParseError::at(
+                &format!("third operand for {} must be a label", operation_name),
+                offset,
+            ))
+        }
+    };
+
+    Ok((first_register, second_register, target))
 }
 
 /// Gets the value stored in the register with the provided name.
-fn get_register_value(name: &str, context: &Context) -> Result<i32, RuntimeError> {
+fn get_register_value(name: &str, context: &Context) -> Result<i32, Error> {
     match context.registers.get(name) {
         Some(x) => Ok(*x),
-        _ => Err(RuntimeError::new(
+        _ => Err(Error::new(
             &format!("invalid register name: {}", name),
-            context,
+            context.current_line_number,
+        )),
+    }
+}
+
+/// Gets the value of an operand that is either a register or a literal.
+fn get_operand_value(operand: &Operand, context: &Context) -> Result<i32, Error> {
+    match operand {
+        Operand::Register(name) => get_register_value(name, context),
+        Operand::Literal(val) => Ok(*val),
+        Operand::Label(name) => Err(Error::new(
+            &format!("label {} does not have a value", name),
+            context.current_line_number,
         )),
     }
 }
@@ -473,43 +1627,184 @@ fn get_register_value(name: &str, context: &Context) -> Result<i32, RuntimeError
 /// A transformation to apply to a register's value.
 enum Transformation {
     Add(i32),
+    Subtract(i32),
     Multiply(i32),
     Divide(i32),
+    Modulo(i32),
+    And(i32),
+    Or(i32),
+    Xor(i32),
+    Not,
+    ShiftLeft(i32),
+    ShiftRight(i32),
+    SquareRoot,
+    Min(i32),
+    Max(i32),
     Set(i32),
 }
 
 /// Modifies the register with the provided name using the provided `Transformation`.
 fn modify_register(name: &str, transformation: Transformation, context: &mut Context) -> OpResult {
-    let mut register = match context.registers.entry(name.to_string()) {
-        Occupied(e) => e,
-        _ => {
-            return Err(RuntimeError::new(
-                &format!("invalid register name: {}", name),
-                context,
+    let value = get_register_value(name, context)?;
+    let overflow = context.overflow;
+
+    let new_value = match transformation {
+        Transformation::Divide(0) | Transformation::Modulo(0) => {
+            return Err(Error::new("division by zero", context.current_line_number))
+        }
+        Transformation::SquareRoot if value < 0 => {
+            return Err(Error::new(
+                &format!("cannot take the square root of {}", value),
+                context.current_line_number,
+            ))
+        }
+        Transformation::ShiftLeft(x) | Transformation::ShiftRight(x) if !(0..32).contains(&x) => {
+            return Err(Error::new(
+                &format!("invalid shift amount: {}", x),
+                context.current_line_number,
             ))
         }
+        Transformation::Add(x) => apply_arithmetic(
+            overflow,
+            value,
+            x,
+            i32::wrapping_add,
+            i32::saturating_add,
+            i32::checked_add,
+        ),
+        Transformation::Subtract(x) => apply_arithmetic(
+            overflow,
+            value,
+            x,
+            i32::wrapping_sub,
+            i32::saturating_sub,
+            i32::checked_sub,
+        ),
+        Transformation::Multiply(x) => apply_arithmetic(
+            overflow,
+            value,
+            x,
+            i32::wrapping_mul,
+            i32::saturating_mul,
+            i32::checked_mul,
+        ),
+        Transformation::Divide(x) => apply_arithmetic(
+            overflow,
+            value,
+            x,
+            i32::wrapping_div,
+            i32::saturating_div,
+            i32::checked_div,
+        )
+        .map(|quotient| {
+            // the quotient was rounded toward zero, which is up instead of down if it's negative and inexact
+            let rounded_up = value.wrapping_rem(x) != 0 && (value < 0) != (x < 0);
+            if context.division == DivisionBehavior::Floor && rounded_up {
+                quotient - 1
+            } else {
+                quotient
+            }
+        }),
+        // the only case that can overflow is `i32::MIN % -1`, which is mathematically 0
+        Transformation::Modulo(x) => {
+            let remainder = value.wrapping_rem(x);
+            // the remainder has the sign of the value, but rounding the quotient down means it has the sign of the divisor instead
+            if context.division == DivisionBehavior::Floor
+                && remainder != 0
+                && (remainder < 0) != (x < 0)
+            {
+                Some(remainder + x)
+            } else {
+                Some(remainder)
+            }
+        }
+        Transformation::And(x) => Some(value & x),
+        Transformation::Or(x) => Some(value | x),
+        Transformation::Xor(x) => Some(value ^ x),
+        Transformation::Not => Some(!value),
+        Transformation::ShiftLeft(x) => {
+            // shifting left multiplies by a power of 2, so it can overflow the same way
+            let shifted = i64::from(value) << x;
+            match overflow {
+                OverflowBehavior::Wrap => Some(value << x),
+                OverflowBehavior::Saturate => {
+                    Some(shifted.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32)
+                }
+                OverflowBehavior::Trap => i32::try_from(shifted).ok(),
+            }
+        }
+        Transformation::ShiftRight(x) => Some(value >> x),
+        Transformation::SquareRoot => Some(integer_square_root(value)),
+        Transformation::Min(x) => Some(std::cmp::min(value, x)),
+        Transformation::Max(x) => Some(std::cmp::max(value, x)),
+        Transformation::Set(x) => Some(x),
     };
 
-    match transformation {
-        Transformation::Add(x) => register.insert(register.get() + x),
-        Transformation::Multiply(x) => register.insert(register.get() * x),
-        Transformation::Divide(x) => register.insert(register.get() / x),
-        Transformation::Set(x) => register.insert(x),
-    };
+    match new_value {
+        Some(x) => {
+            context.registers.insert(name.to_string(), x);
+            Ok(())
+        }
+        None => Err(Error::new(
+            "arithmetic overflow",
+            context.current_line_number,
+        )),
+    }
+}
 
-    Ok(())
+/// Applies an arithmetic operation that can overflow.
+///
+/// # Arguments
+/// * `overflow`: What to do if the result of the operation overflows.
+/// * `value`: The left-hand side of the operation.
+/// * `operand`: The right-hand side of the operation.
+/// * `wrapping`: The operation to use if results should wrap around on overflow.
+/// * `saturating`: The operation to use if results should be clamped on overflow.
+/// * `checked`: The operation to use if overflow should be an error.
+///
+/// Returns `None` if the operation overflowed and overflow should be an error.
+fn apply_arithmetic(
+    overflow: OverflowBehavior,
+    value: i32,
+    operand: i32,
+    wrapping: fn(i32, i32) -> i32,
+    saturating: fn(i32, i32) -> i32,
+    checked: fn(i32, i32) -> Option<i32>,
+) -> Option<i32> {
+    match overflow {
+        OverflowBehavior::Wrap => Some(wrapping(value, operand)),
+        OverflowBehavior::Saturate => Some(saturating(value, operand)),
+        OverflowBehavior::Trap => checked(value, operand),
+    }
 }
 
-/// Sets the provided context's `current_line_number` to the line the provided label is defined on.
-fn jump_to_label(name: &str, mut context: &mut Context) -> OpResult {
-    match context.labels.get(name) {
-        Some(x) => context.current_line_number = *x,
-        _ => {
-            return Err(RuntimeError::new(
-                &format!("unknown label: {}", name),
-                context,
-            ))
-        }
+/// Computes the floor of the square root of a non-negative number using Newton's method.
+fn integer_square_root(value: i32) -> i32 {
+    if value < 2 {
+        return value;
+    }
+
+    let value = value as i64;
+    let mut guess = value;
+    let mut next_guess = (guess + 1) / 2;
+    while next_guess < guess {
+        guess = next_guess;
+        next_guess = (guess + value / guess) / 2;
+    }
+
+    guess as i32
+}
+
+/// Sets the provided context's `current_line_number` to the line the provided jump target's label is defined on.
+fn jump_to_label(target: &JumpTarget, context: &mut Context) {
+    context.current_line_number = target.line_number;
+    context.jumps += 1;
+}
+
+/// Sets the provided context's `current_line_number` to the line the provided jump target's label is defined on if `condition` is true.
+fn jump_to_label_if(condition: bool, target: &JumpTarget, context: &mut Context) -> OpResult {
+    if condition {
+        jump_to_label(target, context);
     }
 
     Ok(())
@@ -0,0 +1,124 @@
+use crate::{clean_line, initial_registers, Context, Error, ParseOptions, Program, Settings};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Runs an interactive session that reads lines of source code from `input` and executes each one as it's entered.
+/// Each line is added to the end of the program so far, so labels stay defined and can be jumped to by later lines.
+/// Lines that can't be parsed are reported and discarded.
+///
+/// # Arguments
+/// * `settings`: Settings that control how the lines are run.
+/// * `input`: Where lines of source code, and input for the program, should be read from.
+/// * `output`: Where prompts, printed values, and changed registers should be written to.
+///
+/// Returns `Err(Error)` if reading from `input` or writing to `output` fails.
+pub fn run<'a>(
+    settings: Settings,
+    input: Box<dyn BufRead + 'a>,
+    output: Box<dyn Write + 'a>,
+) -> Result<(), Error> {
+    let options = ParseOptions::from_settings(&settings)?;
+    let mut source: Vec<String> = Vec::new();
+    let registers = initial_registers(&settings)?;
+    // breakpoints would have to be resolved before any labels are entered, so they're left out
+    let mut context = Context::new(
+        Rc::new(Program::new(Vec::new(), &options)?),
+        &Settings {
+            breakpoints: Vec::new(),
+            ..settings.clone()
+        },
+        input,
+        output,
+    );
+    context.registers = registers;
+    loop {
+        write_output(&mut context, "> ")?;
+        context.flush_output()?;
+
+        let mut line = String::new();
+        match context.input.read_line(&mut line) {
+            Ok(0) => return write_output(&mut context, "\n").and(context.flush_output()),
+            Ok(_) => (),
+            Err(e) => {
+                return Err(Error::new(
+                    &format!("error reading from stdin: {}", e),
+                    source.len(),
+                ))
+            }
+        }
+        let line = clean_line(&line, settings.case_sensitive);
+        if line.is_empty() {
+            continue;
+        }
+
+        source.push(line);
+        let program = match Program::new(source.clone(), &options) {
+            Ok(x) => x,
+            Err(e) => {
+                source.pop();
+                write_output(&mut context, &format!("{}\n", e))?;
+                continue;
+            }
+        };
+        context.set_program(Rc::new(program));
+        context.current_line_number = source.len() - 1;
+
+        let registers_before = context.registers.clone();
+        context.last_printed = None;
+        // each line gets its own step and time limits, so one runaway line doesn't stop every line after it
+        context.steps = 0;
+        context.deadline = context.timeout.map(|timeout| Instant::now() + timeout);
+        let mut result = Ok(());
+        while context.current_line_number < source.len() {
+            result = context
+                .execute_current_line()
+                .map_err(|e| e.with_source(&source));
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if matches!(context.last_printed, Some(c) if c != '\n') {
+            write_output(&mut context, "\n")?;
+        }
+        if let Err(e) = result {
+            write_output(&mut context, &format!("{}\n", e))?;
+        }
+        let changes = describe_changes(
+            &context.register_names,
+            &registers_before,
+            &context.registers,
+        );
+        write_output(&mut context, &changes)?;
+    }
+}
+
+/// Describes the registers whose values are different between two sets of register values, one register per line.
+///
+/// # Arguments
+/// * `register_names`: The names of the registers, in the order to describe them in.
+/// * `before`: Map of register names to their old values.
+/// * `after`: Map of register names to their new values.
+fn describe_changes(
+    register_names: &[String],
+    before: &HashMap<String, i32>,
+    after: &HashMap<String, i32>,
+) -> String {
+    register_names
+        .iter()
+        .filter(|name| before[*name] != after[*name])
+        .map(|name| format!("{} = {}\n", name, after[name]))
+        .collect()
+}
+
+/// Writes a string to the output of a context.
+fn write_output(context: &mut Context, to_write: &str) -> Result<(), Error> {
+    write!(context.output, "{}", to_write).map_err(|e| {
+        Error::new(
+            &format!("error writing to stdout: {}", e),
+            context.current_line_number,
+        )
+    })
+}
@@ -0,0 +1,93 @@
+use serde_json::Value;
+use std::collections::BTreeSet;
+use strategic_communication::{emit_ast, operation_table, Settings};
+
+/// A program that uses every operation.
+const EVERY_OPERATION: &str = "moving forward, start
+going forward, finish
+let budget be Finance
+innovate customer experience
+value-add customer experience
+streamline customer experience
+optimize customer experience
+revamp customer experience
+overhaul customer experience
+reimagine customer experience
+amplify customer experience
+incentivize customer experience
+backburner customer experience
+escalate customer experience with Engineering
+downsize customer experience with revenue streams
+rightsize customer experience
+paradigm shift customer experience
+paradigm shift customer experience to Legal
+sunset customer experience
+align budget with customer experience
+align customer experience with start
+swap customer experience with revenue streams
+reallocate customer experience with revenue streams
+synergize customer experience and revenue streams
+integrate customer experience and revenue streams
+differentiate customer experience and revenue streams
+upscale customer experience with Finance
+scale back customer experience with Finance
+minimize customer experience and revenue streams
+maximize customer experience and revenue streams
+segment customer experience with Legal
+recontextualize customer experience with revenue streams
+consolidate customer experience and revenue streams
+diversify customer experience and revenue streams
+disrupt customer experience and revenue streams
+crowdsource customer experience
+deep dive into customer experience
+brainstorm customer experience
+solicit feedback from customer experience
+audit customer experience
+deliver customer experience
+produce customer experience
+broadcast customer experience and revenue streams
+unpack customer experience
+circle back to start
+revisit start
+reprioritize customer experience
+delegate to start
+report back
+touch base
+wrap up
+pivot customer experience to start
+iterate customer experience to start
+restructure customer experience to start
+capitalize customer experience to start
+harmonize customer experience and budget to start
+outperform customer experience and revenue streams to finish
+";
+
+/// Gets the name of the operation each line of a program was parsed into.
+fn ast_operations(source: &str) -> BTreeSet<String> {
+    let json = emit_ast(source, "every_operation", Settings::default()).unwrap();
+    let ast: Value = serde_json::from_str(&json).unwrap();
+    ast["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line["operation"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn operation_names_match_operation_table() {
+    let table: BTreeSet<String> = operation_table()
+        .into_iter()
+        .map(|operation| operation.name.to_string())
+        .collect();
+    let ast = ast_operations(EVERY_OPERATION);
+    assert!(ast.is_subset(&table), "{:?}", ast.difference(&table));
+
+    // shorthand operations are described as the operation they're shorthand for
+    let shorthand: BTreeSet<String> = ["clear", "add_literal", "subtract_literal"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let expected: BTreeSet<String> = table.difference(&shorthand).cloned().collect();
+    assert_eq!(ast, expected);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use strategic_communication::{run_batch, Settings};
+
+/// Writes a file in a directory.
+fn write(dir: &Path, name: &str, contents: &str) {
+    fs::write(dir.join(name), contents).unwrap();
+}
+
+#[test]
+fn batch_reports_each_program() {
+    let dir = common::temp_dir("batch_reports_each_program");
+    let print_d = "align R&D and Manufacturing with revenue streams\ndeliver revenue streams\n";
+    write(&dir, "a_pass.business", print_d);
+    write(&dir, "a_pass.expected", "D");
+    write(&dir, "b_wrong_output.business", print_d);
+    write(&dir, "b_wrong_output.expected", "E");
+    write(&dir, "c_error.business", "innovate nothing\n");
+    write(&dir, "c_error.expected", "");
+    write(
+        &dir,
+        "d_input.sc",
+        include_str!("../examples/echo.business"),
+    );
+    write(&dir, "d_input.input", "synergy\n");
+    write(&dir, "d_input.expected", "synergy\n");
+    // programs without an expected output aren't run
+    write(&dir, "e_skipped.business", "innovate nothing\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_strategic-communication"))
+        .arg("--batch")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let path = |name: &str| dir.join(name).display().to_string();
+    let expected = format!(
+        "pass\t{}
+fail\t{}\toutput does not match {}
+fail\t{}\t{}:1:10: unknown register: nothing
+pass\t{}
+2 passed, 2 failed
+",
+        path("a_pass.business"),
+        path("b_wrong_output.business"),
+        path("b_wrong_output.expected"),
+        path("c_error.business"),
+        path("c_error.business"),
+        path("d_input.sc"),
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn batch_passes_when_every_program_passes() {
+    let dir = common::temp_dir("batch_passes_when_every_program_passes");
+    write(
+        &dir,
+        "hello.business",
+        include_str!("../examples/hello_world.business"),
+    );
+    write(&dir, "hello.expected", "Hello, World!");
+    assert!(run_batch(&dir.display().to_string(), Settings::default()).unwrap());
+}
+
+#[test]
+fn batch_cannot_record() {
+    let dir = common::temp_dir("batch_cannot_record");
+    let record = dir.join("record.csv");
+    let output = Command::new(env!("CARGO_BIN_EXE_strategic-communication"))
+        .arg("--batch")
+        .arg(&dir)
+        .arg("--record")
+        .arg(&record)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--record"));
+    assert!(!record.exists());
+}
+
+#[test]
+fn run_batch_ignores_record() {
+    let dir = common::temp_dir("run_batch_ignores_record");
+    write(
+        &dir,
+        "hello.business",
+        include_str!("../examples/hello_world.business"),
+    );
+    write(&dir, "hello.expected", "Hello, World!");
+    let record = dir.join("record.csv");
+    let settings = Settings {
+        record: Some(record.display().to_string()),
+        ..Settings::default()
+    };
+    assert!(run_batch(&dir.display().to_string(), settings).unwrap());
+    assert!(!record.exists());
+}
@@ -0,0 +1,224 @@
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the interpreter with some arguments.
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_strategic-communication"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs the interpreter with some arguments, writing some text to its stdin.
+fn run_with_stdin(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_strategic-communication"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Writes a program with a comment and a blank line to a directory, and returns its path.
+fn write_program(dir: &Path) -> String {
+    let path = dir.join("main.business");
+    fs::write(
+        &path,
+        "FYI, prints D\n\nalign R&D and Manufacturing with revenue streams\ndeliver revenue streams\n",
+    )
+    .unwrap();
+    path.display().to_string()
+}
+
+#[test]
+fn trace_shows_original_lines() {
+    let dir = common::temp_dir("trace_shows_original_lines");
+    let path = write_program(&dir);
+    let output = run(&[&path, "--trace"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<&str> = stderr
+        .lines()
+        .map(|line| line.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(lines, vec![format!("{}:3", path), format!("{}:4", path)]);
+}
+
+#[test]
+fn profile_shows_original_lines() {
+    let dir = common::temp_dir("profile_shows_original_lines");
+    let path = write_program(&dir);
+    let output = run(&[&path, "--profile"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr,
+        format!(
+            "executions\tline\tsource
+1\t{0}:3\talign r&d and manufacturing with revenue streams
+1\t{0}:4\tdeliver revenue streams
+",
+            path
+        )
+    );
+}
+
+#[test]
+fn invalid_timeouts_are_rejected() {
+    for timeout in &["-1", "0", "NaN", "inf", "soon"] {
+        let output = run(&[
+            "examples/hello_world.business",
+            &format!("--timeout={}", timeout),
+        ]);
+        assert!(!output.status.success(), "{}", timeout);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("--timeout"), "{}", stderr);
+        assert!(output.stdout.is_empty(), "{}", timeout);
+    }
+}
+
+#[test]
+fn fractional_timeout_is_accepted() {
+    let output = run(&["examples/hello_world.business", "--timeout", "2.5"]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Hello, World!");
+}
+
+#[test]
+fn dump_registers_writes_to_stdout_without_a_path() {
+    let dir = common::temp_dir("dump_registers_writes_to_stdout_without_a_path");
+    let path = write_program(&dir);
+    let output = run(&["--dump-registers", &path]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json = stdout.strip_prefix("D").unwrap();
+    let registers: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(registers["revenue streams"], 68);
+}
+
+#[test]
+fn dump_registers_writes_to_a_path_given_with_equals() {
+    let dir = common::temp_dir("dump_registers_writes_to_a_path_given_with_equals");
+    let path = write_program(&dir);
+    let dump = dir.join("registers.json");
+    let output = run(&[&format!("--dump-registers={}", dump.display()), &path]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"D");
+    let registers: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dump).unwrap()).unwrap();
+    assert_eq!(registers["revenue streams"], 68);
+}
+
+#[test]
+fn errors_exit_with_failure_status() {
+    let dir = common::temp_dir("errors_exit_with_failure_status");
+    let path = dir.join("main.business");
+    fs::write(
+        &path,
+        "align deficit Engineering with revenue streams\ndeliver revenue streams\n",
+    )
+    .unwrap();
+    let output = run(&[&path.display().to_string()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with(&format!("{}:2:", path.display())),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn exceeding_max_steps_exits_with_failure_status() {
+    let dir = common::temp_dir("exceeding_max_steps_exits_with_failure_status");
+    let path = dir.join("main.business");
+    fs::write(&path, "moving forward, again\ncircle back to again\n").unwrap();
+    let output = run(&[&path.display().to_string(), "--max-steps", "100"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("exceeded maximum step count"), "{}", stderr);
+}
+
+#[test]
+fn exceeding_timeout_exits_with_failure_status() {
+    let dir = common::temp_dir("exceeding_timeout_exits_with_failure_status");
+    let path = dir.join("main.business");
+    fs::write(&path, "moving forward, again\ncircle back to again\n").unwrap();
+    let output = run(&[&path.display().to_string(), "--timeout", "0.1"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("exceeded time limit"), "{}", stderr);
+}
+
+#[test]
+fn breakpoint_on_the_last_line_pauses_at_the_end() {
+    let dir = common::temp_dir("breakpoint_on_the_last_line_pauses_at_the_end");
+    let path = dir.join("main.business");
+    fs::write(
+        &path,
+        "align R&D with revenue streams\nmoving forward, done\n",
+    )
+    .unwrap();
+    let output = run_with_stdin(
+        &[&path.display().to_string(), "--break", "done"],
+        "print revenue streams\ncontinue\n",
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("reached breakpoint after label: done\nend of program\n"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("revenue streams = 6"), "{}", stderr);
+}
+
+#[test]
+fn breakpoint_pauses_every_time_the_label_is_reached() {
+    let dir = common::temp_dir("breakpoint_pauses_every_time_the_label_is_reached");
+    let path = dir.join("main.business");
+    fs::write(
+        &path,
+        "align Legal with revenue streams
+moving forward, top
+streamline revenue streams
+iterate revenue streams to top
+",
+    )
+    .unwrap();
+    let output = run_with_stdin(
+        &[&path.display().to_string(), "--break", "top"],
+        "continue\ncontinue\n",
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let pauses: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with(&path.display().to_string()))
+        .collect();
+    assert_eq!(
+        pauses,
+        vec![
+            format!("{}:3: streamline revenue streams", path.display()),
+            format!("{}:3: streamline revenue streams", path.display()),
+        ]
+    );
+}
+
+#[test]
+fn breakpoints_cannot_be_used_in_interactive_mode() {
+    let output = run(&["--repl", "--break", "top"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--repl"), "{}", stderr);
+}
@@ -0,0 +1,147 @@
+// each test file only uses some of these
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::io::Cursor;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use strategic_communication::{emit_c, run_source_with_io, Settings};
+
+/// Runs a program with the interpreter.
+///
+/// # Arguments
+/// * `source`: The source code of the program to run.
+/// * `settings`: Settings that control how the program is run.
+/// * `input`: What the program reads from stdin.
+///
+/// Returns what the program printed, or the first line of the error it stopped with.
+pub fn run(source: &str, settings: Settings, input: &str) -> Result<String, String> {
+    let mut output = Vec::new();
+    let result = run_source_with_io(
+        source,
+        settings,
+        Box::new(Cursor::new(input.as_bytes().to_vec())),
+        Box::new(&mut output),
+    );
+    match result {
+        Ok(()) => Ok(String::from_utf8(output).unwrap()),
+        Err(e) => Err(first_line(&e.to_string())),
+    }
+}
+
+/// Converts a program to C, then compiles and runs it.
+///
+/// # Arguments
+/// * `name`: A name for the program that's unique among the tests.
+/// * `source`: The source code of the program to run.
+/// * `settings`: Settings that control how the program is run.
+/// * `input`: What the program reads from stdin.
+///
+/// Returns what the program printed, or the first line it wrote to stderr if it failed.
+/// Returns `None` if there's no C compiler to compile it with.
+pub fn run_compiled(
+    name: &str,
+    source: &str,
+    settings: Settings,
+    input: &str,
+) -> Option<Result<String, String>> {
+    let c = emit_c(source, settings).unwrap();
+    let output = compile_and_run(name, &c, input)?;
+    if output.status.success() {
+        Some(Ok(String::from_utf8(output.stdout).unwrap()))
+    } else {
+        Some(Err(first_line(&String::from_utf8(output.stderr).unwrap())))
+    }
+}
+
+/// Compiles C source code and runs the result.
+///
+/// # Arguments
+/// * `name`: A name for the program that's unique among the tests.
+/// * `c`: The C source code.
+/// * `input`: What the program reads from stdin.
+///
+/// Returns the output of the program, or `None` if there's no C compiler to compile it with.
+pub fn compile_and_run(name: &str, c: &str, input: &str) -> Option<Output> {
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    if Command::new(&compiler).arg("--version").output().is_err() {
+        eprintln!("skipping {}: no C compiler found", name);
+        return None;
+    }
+
+    let dir = temp_dir(name);
+    let c_path = dir.join(format!("{}.c", name));
+    let exe_path = dir.join(name);
+    fs::write(&c_path, c).unwrap();
+    let compiled = Command::new(&compiler)
+        .arg("-std=c99")
+        .arg("-o")
+        .arg(&exe_path)
+        .arg(&c_path)
+        .output()
+        .unwrap();
+    assert!(
+        compiled.status.success(),
+        "{}",
+        String::from_utf8_lossy(&compiled.stderr)
+    );
+
+    let mut child = Command::new(&exe_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    Some(child.wait_with_output().unwrap())
+}
+
+/// Creates an empty directory for a test to write files to.
+///
+/// # Arguments
+/// * `name`: A name for the directory that's unique among the tests.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Gets the first line of some text.
+fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or("").to_string()
+}
+
+/// Runs a program with the interpreter, and again after compiling it to C if there's a C compiler, and checks that both give the same result.
+///
+/// # Arguments
+/// * `name`: A name for the program that's unique among the tests.
+/// * `source`: The source code of the program to run.
+/// * `settings`: Settings that control how the program is run.
+/// * `input`: What the program reads from stdin.
+///
+/// Returns what the program printed, or the first line of the error it stopped with.
+pub fn run_both(
+    name: &str,
+    source: &str,
+    settings: Settings,
+    input: &str,
+) -> Result<String, String> {
+    let interpreted = run(source, settings.clone(), input);
+    if let Some(compiled) = run_compiled(name, source, settings, input) {
+        assert_eq!(
+            interpreted, compiled,
+            "compiled program behaved differently"
+        );
+    }
+    interpreted
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use std::fs;
+use strategic_communication::Settings;
+
+#[test]
+fn labels_right_after_conditional_jumps() {
+    let source = "align PR with customer experience
+moving forward, loop
+streamline customer experience
+iterate customer experience to loop
+moving forward, after
+pivot customer experience to done
+moving forward, done
+moving forward, finished
+align R&D and Manufacturing with revenue streams
+deliver revenue streams
+";
+    assert_eq!(
+        common::run_both(
+            "labels_after_conditional_jumps",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Ok("D".to_string())
+    );
+}
+
+#[test]
+fn trailing_label_with_no_final_operation() {
+    let source = "align R&D and Manufacturing with revenue streams
+deliver revenue streams
+circle back to the end
+deliver revenue streams
+moving forward, the end
+";
+    assert_eq!(
+        common::run_both("trailing_label", source, Settings::default(), ""),
+        Ok("D".to_string())
+    );
+}
+
+#[test]
+fn errors_report_original_file_and_line() {
+    let dir = common::temp_dir("errors_report_original_file_and_line");
+    let main_path = dir.join("main.business");
+    let lib_path = dir.join("lib.business");
+    fs::write(
+        &main_path,
+        "FYI, jumps to a label that doesn't exist\n\nmoving forward, start\nsee attached lib.business\n",
+    )
+    .unwrap();
+    fs::write(
+        &lib_path,
+        "FYI, sets up the bad line number\n\nalign Finance with customer experience -- no label is on this line\n\nreprioritize customer experience\n",
+    )
+    .unwrap();
+
+    let source = fs::read_to_string(&main_path).unwrap();
+    let settings = Settings {
+        file: Some(main_path.display().to_string()),
+        ..Settings::default()
+    };
+    let expected = format!("{}:5: no label on line 4", lib_path.display());
+    assert_eq!(
+        common::run(&source, settings.clone(), ""),
+        Err(expected.clone())
+    );
+    if let Some(result) = common::run_compiled(
+        "errors_report_original_file_and_line",
+        &source,
+        settings,
+        "",
+    ) {
+        assert_eq!(result, Err(expected));
+    }
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use strategic_communication::Settings;
+
+#[test]
+fn zero_to_nine() {
+    let expected: String = (0..10).map(|n| format!("{}\n", n)).collect();
+    assert_eq!(
+        common::run_both(
+            "zero_to_nine",
+            include_str!("../examples/0to9.business"),
+            Settings::default(),
+            ""
+        ),
+        Ok(expected)
+    );
+}
+
+#[test]
+fn fizzbuzz() {
+    let expected: String = (1..100)
+        .map(|n| match (n % 3, n % 5) {
+            (0, 0) => "FizzBuzz".to_string(),
+            (0, _) => "Fizz".to_string(),
+            (_, 0) => "Buzz".to_string(),
+            _ => n.to_string(),
+        })
+        .map(|line| line + "\n")
+        .collect();
+    assert_eq!(
+        common::run_both(
+            "fizzbuzz",
+            include_str!("../examples/fizzbuzz.business"),
+            Settings::default(),
+            ""
+        ),
+        Ok(expected)
+    );
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use strategic_communication::{run_source, run_source_with_io, Settings};
+
+#[test]
+fn printing_a_negative_value_is_an_error() {
+    let source = "streamline customer experience
+deliver customer experience
+";
+    assert_eq!(
+        common::run_both(
+            "printing_a_negative_value_is_an_error",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:2: -1 does not correspond to a valid UTF-8 character".to_string())
+    );
+}
+
+#[test]
+fn printing_the_last_character_before_surrogates() {
+    // 55295 is 0xD7FF
+    let source = "align Marketing, Marketing, Legal, Executive Management, Marketing with customer experience
+deliver customer experience
+";
+    assert_eq!(
+        common::run_both(
+            "printing_the_last_character_before_surrogates",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Ok("\u{D7FF}".to_string())
+    );
+}
+
+#[test]
+fn printing_a_surrogate_is_an_error() {
+    // 55296 is 0xD800
+    let source =
+        "align Marketing, Marketing, Legal, Executive Management, R&D with customer experience
+deliver customer experience
+";
+    assert_eq!(
+        common::run_both(
+            "printing_a_surrogate_is_an_error",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:2: 55296 does not correspond to a valid UTF-8 character".to_string())
+    );
+}
+
+#[test]
+fn printing_the_first_character_after_surrogates() {
+    // 57344 is 0xE000
+    let source = "align Marketing, Sales, PR, Finance, Finance with customer experience
+deliver customer experience
+";
+    assert_eq!(
+        common::run_both(
+            "printing_the_first_character_after_surrogates",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Ok("\u{E000}".to_string())
+    );
+}
+
+#[test]
+fn read_line_ending_in_eof() {
+    // reads after the end of input store -1, which is 0 after being incremented
+    let source = "brainstorm customer experience
+crowdsource revenue streams
+crowdsource core competencies
+brainstorm best practices
+deliver customer experience
+deliver revenue streams
+innovate core competencies
+innovate best practices
+pivot core competencies to first read checked
+deliver customer experience
+moving forward, first read checked
+pivot best practices to second read checked
+deliver customer experience
+moving forward, second read checked
+";
+    assert_eq!(
+        common::run_both("read_line_ending_in_eof", source, Settings::default(), "hi"),
+        Ok("hi".to_string())
+    );
+}
+
+#[test]
+fn read_line_keeps_the_rest_of_the_line_for_later_reads() {
+    let source = "brainstorm customer experience
+brainstorm revenue streams
+crowdsource core competencies
+brainstorm best practices
+brainstorm stakeholder engagement
+deliver customer experience
+deliver revenue streams
+deliver core competencies
+deliver best practices
+deliver stakeholder engagement
+";
+    assert_eq!(
+        common::run_both(
+            "read_line_keeps_the_rest_of_the_line_for_later_reads",
+            source,
+            Settings::default(),
+            "abc\nd"
+        ),
+        Ok("abc\nd".to_string())
+    );
+}
+
+#[test]
+fn input_and_output_can_be_provided() {
+    let mut output = Vec::new();
+    run_source_with_io(
+        include_str!("../examples/echo.business"),
+        Settings::default(),
+        Box::new(&b"synergy\n"[..]),
+        Box::new(&mut output),
+    )
+    .unwrap();
+    assert_eq!(output, b"synergy\n");
+}
+
+#[test]
+fn errors_use_the_name_of_the_source() {
+    let source = "align deficit Engineering with customer experience
+deliver customer experience
+";
+    let settings = Settings {
+        input: Some(Vec::new()),
+        ..Settings::default()
+    };
+    let error = run_source("main.business", source, settings).unwrap_err();
+    assert!(
+        error.to_string().starts_with("main.business:2: "),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn source_without_a_file_cannot_include_files() {
+    assert_eq!(
+        common::run("see attached examples/hello_world.business", Settings::default(), ""),
+        Err("<source>:1: cannot include examples/hello_world.business from source code that wasn't read from a file".to_string())
+    );
+}
+
+#[test]
+fn print_hex() {
+    // the most negative value is -2^15 doubled 16 times
+    let min = format!(
+        "align deficit PR, Legal, Sales, R&D, Manufacturing with customer experience\n{}",
+        "amplify customer experience\n".repeat(16)
+    );
+    for (name, setup, expected) in [
+        ("print_hex_zero", "", "0x0"),
+        (
+            "print_hex_positive",
+            "align PR, Engineering with customer experience\n",
+            "0x1f",
+        ),
+        (
+            "print_hex_negative",
+            "align deficit Engineering, HR with customer experience\n",
+            "-0xa",
+        ),
+        ("print_hex_min", &min, "-0x80000000"),
+    ] {
+        let source = format!("{}unpack customer experience", setup);
+        assert_eq!(
+            common::run_both(name, &source, Settings::default(), ""),
+            Ok(expected.to_string())
+        );
+    }
+}
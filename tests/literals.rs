@@ -0,0 +1,135 @@
+mod common;
+
+use std::io::Cursor;
+use strategic_communication::{run_source_with_state, Settings};
+
+/// Runs a program that stores a constant expression in `customer experience`, and gets the value stored.
+///
+/// # Arguments
+/// * `expression`: The constant expression.
+/// * `settings`: Settings that control how the program is run.
+fn value_of(expression: &str, settings: Settings) -> i32 {
+    let source = format!("align {} with customer experience", expression);
+    let mut output = Vec::new();
+    let registers = run_source_with_state(
+        &source,
+        settings,
+        Box::new(Cursor::new(Vec::new())),
+        Box::new(&mut output),
+    )
+    .unwrap();
+    registers["customer experience"]
+}
+
+#[test]
+fn multi_digit_literal() {
+    // 90 is 'Z'
+    let source = "align executive management and hr with customer experience
+deliver customer experience
+";
+    assert_eq!(
+        common::run(source, Settings::default(), ""),
+        Ok("Z".to_string())
+    );
+}
+
+#[test]
+fn negative_multi_digit_literals() {
+    assert_eq!(
+        value_of("deficit Engineering and Marketing", Settings::default()),
+        -15
+    );
+    assert_eq!(
+        value_of("deficit Finance, HR, and Sales", Settings::default()),
+        -407
+    );
+    assert_eq!(
+        value_of("deficit HR and Executive Management", Settings::default()),
+        -9
+    );
+}
+
+/// Gets settings that read constant expressions in base 16.
+fn hex() -> Settings {
+    Settings {
+        hex: true,
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn hex_literals() {
+    assert_eq!(value_of("Facilities and Facilities", hex()), 0xFF);
+    assert_eq!(value_of("Engineering, HR", hex()), 0x10);
+    assert_eq!(
+        value_of("deficit Operations and Customer Success", hex()),
+        -0xAE
+    );
+    // without --hex, digits are decimal
+    assert_eq!(value_of("Engineering, HR", Settings::default()), 10);
+}
+
+#[test]
+fn hex_constants_need_hex_mode() {
+    assert_eq!(
+        common::run("align Facilities with assets", Settings::default(), ""),
+        Err("<source>:1:7: unknown register: facilities".to_string())
+    );
+}
+
+#[test]
+fn three_digit_literal_is_one_operand() {
+    assert_eq!(
+        value_of("Engineering, Legal, and PR", Settings::default()),
+        123
+    );
+    assert_eq!(
+        value_of("Engineering and Legal and PR", Settings::default()),
+        123
+    );
+    assert_eq!(value_of("Sales, HR, HR", Settings::default()), 700);
+}
+
+/// The constants for the largest value a register can hold, 2147483647, without the last one.
+const ALMOST_MAX: &str =
+    "Legal, Engineering, Finance, Sales, Finance, Manufacturing, PR, R&D, Finance";
+
+#[test]
+fn largest_literal() {
+    let max = format!("{}, Sales", ALMOST_MAX);
+    assert_eq!(value_of(&max, Settings::default()), i32::MAX);
+    assert_eq!(
+        value_of(&format!("deficit {}", max), Settings::default()),
+        -i32::MAX
+    );
+}
+
+#[test]
+fn literal_too_large_for_a_register() {
+    let one_more = format!(
+        "touch base\nalign {}, Manufacturing with assets",
+        ALMOST_MAX
+    );
+    assert_eq!(
+        common::run(&one_more, Settings::default(), ""),
+        Err("<source>:2:7: literal is too large to fit in a register".to_string())
+    );
+
+    let twelve_constants = "touch base
+align Engineering, Legal, PR, Finance, Marketing, R&D, Sales, Manufacturing, Executive Management, HR, Engineering, Legal with assets";
+    assert_eq!(
+        common::run(twelve_constants, Settings::default(), ""),
+        Err("<source>:2:7: literal is too large to fit in a register".to_string())
+    );
+}
+
+#[test]
+fn leading_zeros_do_not_make_a_literal_too_large() {
+    assert_eq!(
+        value_of(
+            "HR, HR, HR, HR, HR, HR, HR, HR, HR, HR, HR, Engineering",
+            Settings::default()
+        ),
+        1
+    );
+}
@@ -0,0 +1,87 @@
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Opens a document in the language server and gets the diagnostics it publishes for it.
+fn diagnostics(text: &str) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_strategic-communication"))
+        .arg("--lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let open = json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": "untitled:test", "text": text } },
+    });
+    let exit = json!({ "jsonrpc": "2.0", "method": "exit" });
+    let mut stdin = child.stdin.take().unwrap();
+    for message in &[open, exit] {
+        let body = message.to_string();
+        write!(stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let body = &output[output.find("\r\n\r\n").unwrap() + 4..];
+    let message: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(message["method"], "textDocument/publishDiagnostics");
+    message["params"]["diagnostics"].as_array().unwrap().clone()
+}
+
+#[test]
+fn duplicate_labels_are_errors_at_later_definitions() {
+    let diagnostics = diagnostics(
+        "moving forward, synergy\ncircle back to synergy\n\ngoing forward, synergy\nmoving forward, synergy\n",
+    );
+    let errors: Vec<(u64, &str)> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic["severity"] == 1)
+        .map(|diagnostic| {
+            (
+                diagnostic["range"]["start"]["line"].as_u64().unwrap(),
+                diagnostic["message"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (3, "label is already defined at <source>:1: synergy"),
+            (4, "label is already defined at <source>:1: synergy"),
+        ]
+    );
+}
+
+#[test]
+fn unique_labels_are_not_errors() {
+    let diagnostics = diagnostics("moving forward, synergy\ncircle back to synergy\n");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn every_line_with_an_error_is_reported() {
+    let diagnostics = diagnostics(
+        "innovate revenue stream\ndeliver revenue streams\n\nsynergize\ncircle back to nowhere\n",
+    );
+    let errors: Vec<u64> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic["severity"] == 1)
+        .map(|diagnostic| diagnostic["range"]["start"]["line"].as_u64().unwrap())
+        .collect();
+    assert_eq!(errors, vec![0, 3, 4]);
+    assert_eq!(
+        diagnostics[2]["message"].as_str().unwrap(),
+        "unknown label: nowhere"
+    );
+}
+
+#[test]
+fn empty_document_has_no_diagnostics() {
+    for text in &["", "\n\n", "FYI, nothing yet\n"] {
+        let diagnostics = diagnostics(text);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+}
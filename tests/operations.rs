@@ -0,0 +1,346 @@
+mod common;
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use strategic_communication::{run_source_with_state, DivisionBehavior, Settings};
+
+/// Runs a program with the interpreter and gets the values in its registers when it finishes.
+///
+/// # Arguments
+/// * `source`: The source code of the program to run.
+/// * `settings`: Settings that control how the program is run.
+fn final_registers(source: &str, settings: Settings) -> HashMap<String, i32> {
+    let mut output = Vec::new();
+    run_source_with_state(
+        source,
+        settings,
+        Box::new(Cursor::new(Vec::new())),
+        Box::new(&mut output),
+    )
+    .unwrap()
+}
+
+#[test]
+fn bitwise_and() {
+    // 119 is 0b1110111 and 107 is 0b1101011
+    let source = "align Engineering, Engineering, Executive Management with customer experience
+align Engineering, HR, Sales with revenue streams
+consolidate customer experience and revenue streams
+deliver customer experience
+deliver revenue streams
+";
+    assert_eq!(
+        common::run(source, Settings::default(), ""),
+        Ok("ck".to_string())
+    );
+}
+
+#[test]
+fn bitwise_and_rejects_literals() {
+    assert_eq!(
+        common::run(
+            "consolidate customer experience and Finance",
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:1:37: second operand for bitwise and must be a register".to_string())
+    );
+}
+
+#[test]
+fn division_by_zero_register_is_an_error_on_its_line() {
+    let source = "align Finance with customer experience
+innovate revenue streams
+streamline revenue streams
+segment customer experience with revenue streams
+";
+    assert_eq!(
+        common::run_both(
+            "division_by_zero_register_is_an_error_on_its_line",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:4: division by zero".to_string())
+    );
+}
+
+#[test]
+fn modulo_by_zero_register_is_an_error_on_its_line() {
+    let source = "align Finance with customer experience
+recontextualize customer experience with revenue streams
+";
+    assert_eq!(
+        common::run_both(
+            "modulo_by_zero_register_is_an_error_on_its_line",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:2: division by zero".to_string())
+    );
+}
+
+#[test]
+fn wrap_up_halts_the_program() {
+    let source = "align R&D and Manufacturing with customer experience
+deliver customer experience
+wrap up
+innovate customer experience
+deliver customer experience
+";
+    assert_eq!(
+        common::run_both("wrap_up_halts_the_program", source, Settings::default(), ""),
+        Ok("D".to_string())
+    );
+}
+
+#[test]
+fn final_state_includes_every_register() {
+    let registers = final_registers(
+        "align Finance and Legal with assets
+innovate revenue streams
+streamline key performance indicators
+",
+        Settings::default(),
+    );
+    let expected: HashMap<String, i32> = vec![
+        ("customer experience", 0),
+        ("revenue streams", 1),
+        ("core competencies", 0),
+        ("best practices", 0),
+        ("stakeholder engagement", 0),
+        ("key performance indicators", -1),
+        ("return on investment", 0),
+        ("assets", 42),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+    assert_eq!(registers, expected);
+}
+
+/// Gets a constant expression for a value.
+fn constant(value: i32) -> String {
+    let digits = [
+        "HR",
+        "Engineering",
+        "Legal",
+        "PR",
+        "Finance",
+        "Marketing",
+        "R&D",
+        "Sales",
+        "Manufacturing",
+        "Executive Management",
+    ];
+    let expression = value
+        .abs()
+        .to_string()
+        .chars()
+        .map(|digit| digits[digit.to_digit(10).unwrap() as usize])
+        .collect::<Vec<&str>>()
+        .join(", ");
+    if value < 0 {
+        format!("deficit {}", expression)
+    } else {
+        expression
+    }
+}
+
+/// Runs an operation with a division behavior, with `customer experience` starting at a value.
+/// Prints `Y` if `customer experience` ends up at the expected value, or `N` if it doesn't.
+fn divide(
+    name: &str,
+    operation: &str,
+    value: i32,
+    division: DivisionBehavior,
+    expected: i32,
+) -> String {
+    let source = format!(
+        "align {} with customer experience
+{}
+align {} with core competencies
+align Sales and Manufacturing with stakeholder engagement
+harmonize customer experience and core competencies to divided
+deliver stakeholder engagement
+wrap up
+moving forward, divided
+align Manufacturing and Executive Management with stakeholder engagement
+deliver stakeholder engagement
+",
+        constant(value),
+        operation,
+        constant(expected)
+    );
+    let settings = Settings {
+        division,
+        ..Settings::default()
+    };
+    common::run_both(name, &source, settings, "").unwrap()
+}
+
+#[test]
+fn floor_division_of_negative_dividends() {
+    let floor = DivisionBehavior::Floor;
+    let segment = "segment customer experience with Legal";
+    assert_eq!(divide("floor_segment_neg", segment, -7, floor, -4), "Y");
+    assert_eq!(divide("floor_segment_pos", segment, 7, floor, 3), "Y");
+    assert_eq!(divide("floor_segment_exact", segment, -8, floor, -4), "Y");
+    let halve = "backburner customer experience";
+    assert_eq!(divide("floor_halve_neg", halve, -3, floor, -2), "Y");
+}
+
+#[test]
+fn truncating_division_of_negative_dividends() {
+    let trunc = DivisionBehavior::Trunc;
+    let segment = "segment customer experience with Legal";
+    assert_eq!(divide("trunc_segment_neg", segment, -7, trunc, -3), "Y");
+    let halve = "backburner customer experience";
+    assert_eq!(divide("trunc_halve_neg", halve, -3, trunc, -1), "Y");
+}
+
+#[test]
+fn add_and_subtract_literals() {
+    let registers = final_registers(
+        "align Engineering, HR with assets
+upscale assets with Finance
+scale back revenue streams with Engineering and Legal
+",
+        Settings::default(),
+    );
+    assert_eq!(registers["assets"], 14);
+    assert_eq!(registers["revenue streams"], -12);
+}
+
+#[test]
+fn add_literal_rejects_registers() {
+    assert_eq!(
+        common::run(
+            "upscale assets with revenue streams",
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:1:21: second operand for add literal must be a literal".to_string())
+    );
+}
+
+#[test]
+fn align_with_label_stores_its_line_number() {
+    let source = "touch base
+moving forward, first
+touch base
+touch base
+moving forward, second
+align customer experience with second
+align revenue streams with first
+";
+    let registers = final_registers(source, Settings::default());
+    assert_eq!(registers["customer experience"], 5);
+    assert_eq!(registers["revenue streams"], 2);
+}
+
+#[test]
+fn align_with_label_then_jump_to_line_number() {
+    let source = "align customer experience with done
+reprioritize customer experience
+moving forward, skipped
+align R&D and Finance with revenue streams
+deliver revenue streams
+moving forward, done
+align R&D and Manufacturing with revenue streams
+deliver revenue streams
+";
+    assert_eq!(
+        common::run_both("align_label_jump", source, Settings::default(), ""),
+        Ok("D".to_string())
+    );
+}
+
+#[test]
+fn jump_to_line_without_a_label_is_an_error() {
+    let source = "moving forward, start
+reprioritize customer experience
+";
+    for (name, value, expected) in [
+        ("jump_line_zero", 0, "<source>:2: no label on line 0"),
+        ("jump_line_not_label", 2, "<source>:2: no label on line 2"),
+        ("jump_line_past_end", 3, "<source>:2: no label on line 3"),
+    ] {
+        let settings = Settings {
+            initial_registers: vec![("customer experience".to_string(), value)],
+            ..Settings::default()
+        };
+        assert_eq!(
+            common::run_both(name, source, settings, ""),
+            Err(expected.to_string())
+        );
+    }
+}
+
+#[test]
+fn floor_modulo_has_the_sign_of_the_divisor() {
+    let floor = DivisionBehavior::Floor;
+    let by_2 = "recontextualize customer experience with Legal";
+    assert_eq!(divide("floor_modulo_neg", by_2, -7, floor, 1), "Y");
+    assert_eq!(divide("floor_modulo_pos", by_2, 7, floor, 1), "Y");
+    assert_eq!(divide("floor_modulo_exact", by_2, -8, floor, 0), "Y");
+    let by_minus_2 = "recontextualize customer experience with deficit Legal";
+    assert_eq!(
+        divide("floor_modulo_neg_divisor", by_minus_2, 7, floor, -1),
+        "Y"
+    );
+    let registers = final_registers(
+        "recontextualize customer experience with deficit Engineering",
+        Settings {
+            division: floor,
+            initial_registers: vec![("customer experience".to_string(), i32::MIN)],
+            ..Settings::default()
+        },
+    );
+    assert_eq!(registers["customer experience"], 0);
+}
+
+#[test]
+fn truncating_modulo_has_the_sign_of_the_value() {
+    let trunc = DivisionBehavior::Trunc;
+    let by_2 = "recontextualize customer experience with Legal";
+    assert_eq!(divide("trunc_modulo_neg", by_2, -7, trunc, -1), "Y");
+    let by_minus_2 = "recontextualize customer experience with deficit Legal";
+    assert_eq!(
+        divide("trunc_modulo_neg_divisor", by_minus_2, 7, trunc, 1),
+        "Y"
+    );
+}
+
+#[test]
+fn quotient_and_remainder_give_back_the_value() {
+    for division in [DivisionBehavior::Trunc, DivisionBehavior::Floor] {
+        for (value, divisor) in [(-7, 2), (7, -2), (-7, -2), (7, 2), (-9, 3)] {
+            let settings = Settings {
+                division,
+                initial_registers: vec![
+                    ("customer experience".to_string(), value),
+                    ("revenue streams".to_string(), value),
+                    ("core competencies".to_string(), divisor),
+                ],
+                ..Settings::default()
+            };
+            let registers = final_registers(
+                "segment customer experience with core competencies
+recontextualize revenue streams with core competencies
+",
+                settings,
+            );
+            let quotient = registers["customer experience"];
+            let remainder = registers["revenue streams"];
+            assert_eq!(
+                quotient * divisor + remainder,
+                value,
+                "{} / {}",
+                value,
+                divisor
+            );
+        }
+    }
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use strategic_communication::{OverflowBehavior, Settings};
+
+/// Gets the source code that sets a register to the largest or smallest value it can hold.
+fn set_to_limit(register: &str, smallest: bool) -> String {
+    // start at 2^16 - 1, since constant expressions can't be that large, and shift a 1 in until every bit but the sign bit is set
+    let mut source = format!(
+        "align R&D, Marketing, Marketing, PR, Marketing with {}\n",
+        register
+    );
+    for _ in 16..31 {
+        source += &format!("amplify {0}\ninnovate {0}\n", register);
+    }
+    if smallest {
+        source += &format!("revamp {0}\nstreamline {0}\n", register);
+    }
+    source
+}
+
+/// Runs an operation with saturating arithmetic, with `customer experience` starting at the largest or smallest value and `revenue streams` starting at 1.
+/// Prints `Y` if `customer experience` ends up at the expected limit, or `N` if it doesn't.
+fn saturate(name: &str, operation: &str, from_smallest: bool, to_smallest: bool) -> String {
+    let source = format!(
+        "{}{}
+innovate revenue streams
+{}
+align Sales and Manufacturing with stakeholder engagement
+harmonize customer experience and core competencies to saturated
+deliver stakeholder engagement
+wrap up
+moving forward, saturated
+align Manufacturing and Executive Management with stakeholder engagement
+deliver stakeholder engagement
+",
+        set_to_limit("customer experience", from_smallest),
+        set_to_limit("core competencies", to_smallest),
+        operation
+    );
+    let settings = Settings {
+        overflow: OverflowBehavior::Saturate,
+        ..Settings::default()
+    };
+    common::run_both(name, &source, settings, "").unwrap()
+}
+
+#[test]
+fn saturate_at_max() {
+    for (name, operation) in [
+        ("saturate_increment", "innovate customer experience"),
+        (
+            "saturate_add",
+            "synergize customer experience and revenue streams",
+        ),
+        ("saturate_double", "amplify customer experience"),
+    ] {
+        assert_eq!(
+            saturate(name, operation, false, false),
+            "Y",
+            "{}",
+            operation
+        );
+    }
+}
+
+#[test]
+fn saturate_at_min() {
+    for (name, operation, to_smallest) in [
+        ("saturate_decrement", "streamline customer experience", true),
+        (
+            "saturate_subtract",
+            "differentiate customer experience and revenue streams",
+            true,
+        ),
+        // -i32::MIN is one more than i32::MAX
+        ("saturate_negate", "revamp customer experience", false),
+    ] {
+        assert_eq!(
+            saturate(name, operation, true, to_smallest),
+            "Y",
+            "{}",
+            operation
+        );
+    }
+}
+
+/// Runs a program with an overflow behavior.
+fn run_with_overflow(
+    name: &str,
+    source: &str,
+    overflow: OverflowBehavior,
+) -> Result<String, String> {
+    let settings = Settings {
+        overflow,
+        ..Settings::default()
+    };
+    common::run_both(name, source, settings, "")
+}
+
+/// Shifts 1 left by 31 bits, which is one more than the largest value a register can hold, and prints the result.
+const SHIFT_TO_OVERFLOW: &str = "align Engineering with customer experience
+escalate customer experience with PR and Engineering
+unpack customer experience
+";
+
+#[test]
+fn shift_left_wraps() {
+    assert_eq!(
+        run_with_overflow(
+            "shift_left_wraps",
+            SHIFT_TO_OVERFLOW,
+            OverflowBehavior::Wrap
+        ),
+        Ok("-0x80000000".to_string())
+    );
+}
+
+#[test]
+fn shift_left_saturates() {
+    assert_eq!(
+        run_with_overflow(
+            "shift_left_saturates",
+            SHIFT_TO_OVERFLOW,
+            OverflowBehavior::Saturate
+        ),
+        Ok("0x7fffffff".to_string())
+    );
+}
+
+#[test]
+fn shift_left_traps() {
+    assert_eq!(
+        run_with_overflow(
+            "shift_left_traps",
+            SHIFT_TO_OVERFLOW,
+            OverflowBehavior::Trap
+        ),
+        Err("<source>:2: arithmetic overflow".to_string())
+    );
+}
+
+#[test]
+fn shift_left_without_overflow_is_unchanged() {
+    let source = "align deficit Legal with customer experience
+escalate customer experience with Engineering and HR
+unpack customer experience
+";
+    for overflow in [
+        OverflowBehavior::Wrap,
+        OverflowBehavior::Saturate,
+        OverflowBehavior::Trap,
+    ] {
+        let name = format!("shift_left_without_overflow_{:?}", overflow);
+        assert_eq!(
+            run_with_overflow(&name, source, overflow),
+            Ok("-0x800".to_string())
+        );
+    }
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use strategic_communication::{emit_ast, parse, Settings};
+
+#[test]
+fn labels_named_like_operations_are_only_labels() {
+    let source = "align PR with customer experience
+moving forward, circle back
+pivot customer experience to revisit done
+align R&D and Finance with revenue streams
+synergize revenue streams and customer experience
+deliver revenue streams
+streamline customer experience
+circle back to circle back
+moving forward, revisit done
+align Engineering and HR with revenue streams
+deliver revenue streams
+";
+    assert_eq!(
+        common::run_both(
+            "labels_named_like_operations",
+            source,
+            Settings::default(),
+            ""
+        ),
+        Ok("CBA\n".to_string())
+    );
+}
+
+#[test]
+fn comments_are_ignored() {
+    let source = "FYI, prints an exclamation mark
+align PR and PR with customer experience -- 33 is !
+fyi, the case of the prefix doesn't matter
+deliver customer experience -- print it
+";
+    assert_eq!(
+        common::run(source, Settings::default(), ""),
+        Ok("!".to_string())
+    );
+}
+
+#[test]
+fn comments_cannot_define_labels() {
+    let source = "FYI, moving forward, commented out
+moving forward, kept -- moving forward, also commented out
+circle back to commented out
+";
+    let error = common::run(source, Settings::default(), "").unwrap_err();
+    assert!(error.ends_with("unknown label: commented out"), "{}", error);
+}
+
+#[test]
+fn misspelled_register_is_unknown_register() {
+    assert_eq!(
+        common::run(
+            "synergize customer experiance and revenue streams",
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:1:11: unknown register: customer experiance".to_string())
+    );
+    assert_eq!(
+        common::run(
+            "align customer experiance with revenue streams",
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:1:7: unknown register: customer experiance".to_string())
+    );
+}
+
+#[test]
+fn misspelled_register_copied_from_is_unknown_register_when_strict() {
+    let source = "align revenue streams with customer experiance";
+    assert_eq!(
+        common::run(source, Settings::default(), ""),
+        Err("<source>:1: unknown label: customer experiance".to_string())
+    );
+    let settings = Settings {
+        strict: true,
+        ..Settings::default()
+    };
+    assert_eq!(
+        common::run(source, settings, ""),
+        Err("<source>:1:28: unknown register: customer experiance".to_string())
+    );
+}
+
+#[test]
+fn line_endings_do_not_matter() {
+    let lines = [
+        "moving forward, synergy",
+        "innovate customer experience",
+        "FYI, a comment",
+        "",
+        "iterate customer experience to synergy",
+    ];
+    let lf = lines.join("\n");
+    let mixed = format!(
+        "{}\r\n{}\r{}\n{}\r\r{}\r\n",
+        lines[0], lines[1], lines[2], lines[3], lines[4]
+    );
+    let ast = |source: &str| emit_ast(source, "line_endings", Settings::default()).unwrap();
+    assert_eq!(ast(&mixed), ast(&lf));
+}
+
+#[test]
+fn empty_label_is_rejected() {
+    for source in &[
+        "moving forward,",
+        "going forward,   ",
+        "moving forward, -- later",
+    ] {
+        assert_eq!(
+            common::run(source, Settings::default(), ""),
+            Err("<source>:1: unexpected expression".to_string()),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn label_named_like_a_register_or_literal_is_rejected() {
+    assert_eq!(
+        common::run("moving forward, assets", Settings::default(), ""),
+        Err("<source>:1:17: invalid label name: assets".to_string())
+    );
+    assert_eq!(
+        common::run("going forward, finance", Settings::default(), ""),
+        Err("<source>:1:16: invalid label name: finance".to_string())
+    );
+}
+
+#[test]
+fn label_starting_with_a_register_is_rejected() {
+    assert_eq!(
+        common::run(
+            "going forward, assets review\ncircle back to assets review",
+            Settings::default(),
+            ""
+        ),
+        Err("<source>:1:16: invalid label name: assets review".to_string())
+    );
+}
+
+#[test]
+fn label_starting_with_part_of_a_register_name_can_be_jumped_to() {
+    let source = "circle back to customer journey
+innovate customer experience
+moving forward, customer journey
+align R&D and Manufacturing with revenue streams
+deliver revenue streams
+";
+    assert_eq!(
+        common::run(source, Settings::default(), ""),
+        Ok("D".to_string())
+    );
+    let program = parse(source, Settings::default()).unwrap();
+    assert_eq!(program.label_names(), vec!["customer journey"]);
+}
+
+#[test]
+fn longest_register_name_wins() {
+    let settings = Settings {
+        extra_registers: vec!["pipeline".to_string(), "pipeline health".to_string()],
+        initial_registers: vec![
+            ("pipeline".to_string(), 1),
+            ("pipeline health".to_string(), 64),
+        ],
+        ..Settings::default()
+    };
+    let source = "synergize pipeline health and pipeline
+deliver pipeline health
+";
+    assert_eq!(common::run(source, settings, ""), Ok("A".to_string()));
+}